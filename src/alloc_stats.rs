@@ -0,0 +1,179 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Allocations done during the execution of some code.
+///
+/// Only available when built with the `alloc-stats` feature, which installs a counting global
+/// allocator (a bit slower, so it's opt-in).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub bytes_allocated: usize,
+    /// Peak of memory allocated & not yet freed, relative to the start of the measure
+    pub peak_live_bytes: usize,
+}
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{allocations} allocs, {allocated} allocated, {peak} peak",
+            allocations = self.allocations,
+            allocated = format_bytes(self.bytes_allocated),
+            peak = format_bytes(self.peak_live_bytes),
+        )
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit_idx = 0;
+    while value >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit_idx += 1;
+    }
+    if unit_idx == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {unit}", unit = UNITS[unit_idx])
+    }
+}
+
+/// Run the given function, measuring its allocations (when the `alloc-stats` feature is enabled).
+pub fn measure<T>(func: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    #[cfg(feature = "alloc-stats")]
+    {
+        let (result, stats) = counting::measure(func);
+        (result, Some(stats))
+    }
+    #[cfg(not(feature = "alloc-stats"))]
+    {
+        (func(), None)
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::AllocStats;
+
+    // NOTE: Counters are per thread, so parts running in parallel don't see each other's
+    //   allocations. Memory freed by another thread than the one that allocated it is not accurate,
+    //   but that's not something our solutions do.
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        static BYTES_ALLOCATED: Cell<usize> = const { Cell::new(0) };
+        static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+        static PEAK_LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+    }
+
+    fn on_alloc(size: usize) {
+        // NOTE: `try_with` as thread locals may already be destroyed when a thread exits
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        let _ = BYTES_ALLOCATED.try_with(|bytes| bytes.set(bytes.get() + size));
+        on_live_change(size as isize);
+    }
+
+    fn on_live_change(delta: isize) {
+        let _ = LIVE_BYTES.try_with(|live| {
+            let new_live = live.get() + delta;
+            live.set(new_live);
+            let _ = PEAK_LIVE_BYTES.try_with(|peak| peak.set(peak.get().max(new_live)));
+        });
+    }
+
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = unsafe { System.alloc(layout) };
+            if !ptr.is_null() {
+                on_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = unsafe { System.alloc_zeroed(layout) };
+            if !ptr.is_null() {
+                on_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) };
+            on_live_change(-(layout.size() as isize));
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+            if !new_ptr.is_null() {
+                // Counted as a new allocation of the new size, replacing the old one
+                on_alloc(new_size);
+                on_live_change(-(layout.size() as isize));
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    pub fn measure<T>(func: impl FnOnce() -> T) -> (T, AllocStats) {
+        let allocations_before = ALLOCATIONS.get();
+        let bytes_before = BYTES_ALLOCATED.get();
+        let live_before = LIVE_BYTES.get();
+        PEAK_LIVE_BYTES.set(live_before);
+
+        let result = func();
+
+        let stats = AllocStats {
+            allocations: ALLOCATIONS.get() - allocations_before,
+            bytes_allocated: BYTES_ALLOCATED.get() - bytes_before,
+            peak_live_bytes: (PEAK_LIVE_BYTES.get() - live_before).max(0) as usize,
+        };
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_measure_allocations() {
+        let (sum, stats) = measure(|| {
+            let first: Vec<u64> = Vec::with_capacity(100);
+            drop(first);
+            let second: Vec<u64> = (0..50).collect();
+            second.iter().sum::<u64>()
+        });
+        assert_eq!(sum, 1225);
+        let stats = stats.unwrap();
+        assert_eq!(stats.allocations, 2);
+        assert_eq!(stats.bytes_allocated, 150 * 8);
+        assert_eq!(stats.peak_live_bytes, 100 * 8);
+    }
+
+    #[cfg(not(feature = "alloc-stats"))]
+    #[test]
+    fn test_measure_without_feature() {
+        let (result, stats) = measure(|| vec![1, 2, 3].len());
+        assert_eq!(result, 3);
+        assert_eq!(stats, None);
+    }
+}
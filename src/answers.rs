@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Where expected answers are stored, next to the inputs.
+pub fn answers_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("inputs/answers.toml")
+}
+
+/// Short & stable identifier of an input, used to key answers.
+///
+/// NOTE: Each account gets a different input, answers recorded for our input must never be
+///   compared against the results of another input.
+pub fn input_hash(input: &str) -> String {
+    let digest = Sha256::digest(input.as_bytes());
+    digest.iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartAnswer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<usize>,
+    /// Values known to be wrong (learned from wrong submissions, while the answer is not known yet)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrong: Vec<usize>,
+    /// Lowest value known to be too high
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub too_high: Option<usize>,
+    /// Highest value known to be too low
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub too_low: Option<usize>,
+}
+impl PartAnswer {
+    pub fn add_hint(&mut self, hint: Hint, value: usize) {
+        match hint {
+            Hint::Wrong => {
+                if !self.wrong.contains(&value) {
+                    self.wrong.push(value);
+                    self.wrong.sort();
+                }
+            }
+            Hint::TooHigh => {
+                self.too_high = Some(self.too_high.map_or(value, |bound| bound.min(value)));
+            }
+            Hint::TooLow => {
+                self.too_low = Some(self.too_low.map_or(value, |bound| bound.max(value)));
+            }
+        }
+    }
+
+    /// Check a result against the known hints (does not look at the exact answer)
+    pub fn check_hints(&self, value: usize) -> HintCheck {
+        if self.wrong.contains(&value) {
+            return HintCheck::KnownWrong;
+        }
+        match (self.too_low, self.too_high) {
+            (_, Some(too_high)) if too_high <= value => HintCheck::OutOfBounds,
+            (Some(too_low), _) if value <= too_low => HintCheck::OutOfBounds,
+            _ => HintCheck::Plausible,
+        }
+    }
+
+    pub fn has_hints(&self) -> bool {
+        !self.wrong.is_empty() || self.too_high.is_some() || self.too_low.is_some()
+    }
+
+    /// Human description of the known bounds, like `1234 < ? < 5678`
+    pub fn bounds_description(&self) -> String {
+        let low = self.too_low.map(|bound| format!("{bound} < ")).unwrap_or_default();
+        let high = self.too_high.map(|bound| format!(" < {bound}")).unwrap_or_default();
+        format!("{low}?{high}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+impl std::str::FromStr for Hint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> AnyResult<Self> {
+        match s {
+            "wrong" => Ok(Hint::Wrong),
+            "too-high" => Ok(Hint::TooHigh),
+            "too-low" => Ok(Hint::TooLow),
+            _ => anyhow::bail!("Unknown hint '{s}', expected one of: wrong, too-high, too-low"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintCheck {
+    KnownWrong,
+    OutOfBounds,
+    Plausible,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputAnswers {
+    /// Where the input was found when recorded (informative only, the key is the input hash)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(default)]
+    pub part1: PartAnswer,
+    #[serde(default)]
+    pub part2: PartAnswer,
+}
+impl InputAnswers {
+    pub fn part(&self, part_num: u8) -> &PartAnswer {
+        match part_num {
+            1 => &self.part1,
+            2 => &self.part2,
+            _ => unreachable!("a day only has 2 parts"),
+        }
+    }
+
+    pub fn part_mut(&mut self, part_num: u8) -> &mut PartAnswer {
+        match part_num {
+            1 => &mut self.part1,
+            2 => &mut self.part2,
+            _ => unreachable!("a day only has 2 parts"),
+        }
+    }
+}
+
+/// All known answers, by day name then by input hash.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers {
+    days: BTreeMap<String, BTreeMap<String, InputAnswers>>,
+}
+impl Answers {
+    pub fn from_toml(content: &str) -> AnyResult<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn to_toml(&self) -> AnyResult<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Load answers from the answers file, a missing file means no known answers.
+    pub fn load() -> AnyResult<Self> {
+        let path = answers_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read answers file {}", path.display()))?;
+        Self::from_toml(&content)
+            .with_context(|| format!("Failed to parse answers file {}", path.display()))
+    }
+
+    pub fn save(&self) -> AnyResult<()> {
+        let path = answers_path();
+        fs::write(&path, self.to_toml()?)
+            .with_context(|| format!("Failed to write answers file {}", path.display()))
+    }
+
+    pub fn get(&self, day_name: &str, input: &str) -> Option<&InputAnswers> {
+        self.days.get(day_name)?.get(&input_hash(input))
+    }
+
+    pub fn entry(&mut self, day_name: &str, input: &str) -> &mut InputAnswers {
+        self.days
+            .entry(day_name.to_string())
+            .or_default()
+            .entry(input_hash(input))
+            .or_default()
+    }
+
+    pub fn part(&self, day_name: &str, input: &str, part_num: u8) -> Option<&PartAnswer> {
+        Some(self.get(day_name, input)?.part(part_num))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_hash_is_stable() {
+        assert_eq!(input_hash("1   2\n"), input_hash("1   2\n"));
+        assert_ne!(input_hash("1   2\n"), input_hash("1   3\n"));
+        assert_eq!(input_hash("").len(), 16);
+    }
+
+    #[test]
+    fn test_answers_are_keyed_by_input() {
+        let mut answers = Answers::default();
+        answers.entry("day01", "our input").part1.answer = Some(42);
+
+        let expected = |day_name, input, part_num| {
+            answers.part(day_name, input, part_num).and_then(|part| part.answer)
+        };
+        assert_eq!(expected("day01", "our input", 1), Some(42));
+        assert_eq!(expected("day01", "our input", 2), None);
+        assert_eq!(expected("day01", "another input", 1), None);
+        assert_eq!(expected("day02", "our input", 1), None);
+    }
+
+    #[test]
+    fn test_hints_keep_tightest_bounds() {
+        let mut part = PartAnswer::default();
+        part.add_hint(Hint::TooHigh, 500);
+        part.add_hint(Hint::TooHigh, 800);
+        part.add_hint(Hint::TooLow, 100);
+        part.add_hint(Hint::TooLow, 50);
+        part.add_hint(Hint::Wrong, 300);
+        part.add_hint(Hint::Wrong, 300);
+        assert_eq!(part.too_high, Some(500));
+        assert_eq!(part.too_low, Some(100));
+        assert_eq!(part.wrong, vec![300]);
+        assert_eq!(part.bounds_description(), "100 < ? < 500");
+    }
+
+    #[test]
+    fn test_check_hints() {
+        let mut part = PartAnswer::default();
+        assert_eq!(part.check_hints(42), HintCheck::Plausible);
+
+        part.add_hint(Hint::TooHigh, 500);
+        part.add_hint(Hint::TooLow, 100);
+        part.add_hint(Hint::Wrong, 300);
+        assert_eq!(part.check_hints(300), HintCheck::KnownWrong);
+        assert_eq!(part.check_hints(500), HintCheck::OutOfBounds);
+        assert_eq!(part.check_hints(501), HintCheck::OutOfBounds);
+        assert_eq!(part.check_hints(100), HintCheck::OutOfBounds);
+        assert_eq!(part.check_hints(99), HintCheck::OutOfBounds);
+        assert_eq!(part.check_hints(101), HintCheck::Plausible);
+        assert_eq!(part.check_hints(499), HintCheck::Plausible);
+    }
+
+    #[test]
+    fn test_toml_roundtrip() {
+        let mut answers = Answers::default();
+        let entry = answers.entry("day01", "our input");
+        entry.input = Some("inputs/day01.txt".to_string());
+        entry.part1.answer = Some(42);
+        entry.part2.add_hint(Hint::Wrong, 1234);
+        entry.part2.add_hint(Hint::TooLow, 1000);
+
+        let content = answers.to_toml().unwrap();
+        dbg!(&content);
+        assert_eq!(Answers::from_toml(&content).unwrap(), answers);
+    }
+}
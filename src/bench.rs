@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+
+use crate::alloc_stats::AllocStats;
+use crate::days::Day;
+use crate::runner::{default_input, execute_part};
+
+/// Local history of benchmark results (not versioned, timings depend on the machine).
+pub fn history_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("bench_history.toml")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartBench {
+    pub runs: usize,
+    pub median_ns: u64,
+    pub min_ns: u64,
+    /// Allocations of a single run (only with the `alloc-stats` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alloc: Option<AllocStats>,
+}
+impl PartBench {
+    pub fn from_durations(mut durations: Vec<Duration>) -> Self {
+        assert!(!durations.is_empty(), "need at least 1 run to bench");
+        durations.sort();
+        Self {
+            runs: durations.len(),
+            median_ns: durations[durations.len() / 2].as_nanos() as u64,
+            min_ns: durations[0].as_nanos() as u64,
+            alloc: None,
+        }
+    }
+}
+
+/// Results by day name, then by part name
+pub type BenchResults = BTreeMap<String, BTreeMap<String, PartBench>>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitBench {
+    /// Unix timestamp of the last recording for this commit
+    pub recorded_at: u64,
+    pub days: BenchResults,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchHistory {
+    pub commits: BTreeMap<String, CommitBench>,
+}
+impl BenchHistory {
+    pub fn load() -> AnyResult<Self> {
+        let path = history_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read bench history {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse bench history {}", path.display()))
+    }
+
+    pub fn save(&self) -> AnyResult<()> {
+        let path = history_path();
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("Failed to write bench history {}", path.display()))
+    }
+
+    /// Merge new results in the history of the given commit (other days' results are kept)
+    pub fn record(&mut self, commit: &str, results: &BenchResults) {
+        let commit_bench = self.commits.entry(commit.to_string()).or_default();
+        commit_bench.recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        for (day_name, parts) in results {
+            commit_bench.days.insert(day_name.clone(), parts.clone());
+        }
+    }
+
+    /// Most recently recorded commit, other than the given one
+    pub fn latest_other_commit(&self, commit: &str) -> Option<&str> {
+        self.commits.iter()
+            .filter(|(other, _)| other.as_str() != commit)
+            .max_by_key(|(_, commit_bench)| commit_bench.recorded_at)
+            .map(|(other, _)| other.as_str())
+    }
+}
+
+/// Identifier of the current code: the git commit, with a `-dirty` suffix for uncommitted changes.
+pub fn current_commit() -> AnyResult<String> {
+    let git = |args: &[&str]| -> AnyResult<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .context("Failed to run git")?;
+        anyhow::ensure!(output.status.success(), "git {} failed", args.join(" "));
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let commit = git(&["rev-parse", "--short", "HEAD"])?;
+    let is_dirty = !git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty();
+    Ok(if is_dirty { format!("{commit}-dirty") } else { commit })
+}
+
+pub fn bench_day(day: &Day, num_runs: usize) -> BTreeMap<String, PartBench> {
+    let mut parts = BTreeMap::new();
+    let input = default_input(day);
+    for (part_name, part) in [("part1", &day.part1), ("part2", &day.part2)] {
+        let runs: Vec<_> = (0..num_runs).map(|_| execute_part(part, &input)).collect();
+        if !matches!(runs[0].result, Ok(Some(_))) {
+            continue; // not implemented or failing, nothing to bench
+        }
+        let mut bench = PartBench::from_durations(runs.iter().map(|run| run.duration).collect());
+        bench.alloc = runs[0].alloc;
+        println!(
+            "-- {day_name} {part_name}: median {median:?}, min {min:?} ({num_runs} runs)",
+            day_name = day.name,
+            median = Duration::from_nanos(bench.median_ns),
+            min = Duration::from_nanos(bench.min_ns),
+        );
+        if let Some(alloc) = bench.alloc {
+            println!("   ↳ {alloc}");
+        }
+        parts.insert(part_name.to_string(), bench);
+    }
+    parts
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub day_name: String,
+    pub part_name: String,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+    /// Relative change of the median time, `0.5` means 50% slower
+    pub change: f64,
+}
+impl Comparison {
+    /// Whether it's slower than the threshold (`0.2` for 20% slower)
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.change > threshold
+    }
+}
+
+/// Compare median times of current results against a baseline.
+/// Parts missing in one of the sides are ignored.
+pub fn compare_results(
+    baseline: &CommitBench,
+    current: &BenchResults,
+) -> Vec<Comparison> {
+    let mut comparisons = vec![];
+    for (day_name, parts) in current {
+        let Some(baseline_parts) = baseline.days.get(day_name) else { continue };
+        for (part_name, bench) in parts {
+            let Some(baseline_bench) = baseline_parts.get(part_name) else { continue };
+            comparisons.push(Comparison {
+                day_name: day_name.clone(),
+                part_name: part_name.clone(),
+                baseline_ns: baseline_bench.median_ns,
+                current_ns: bench.median_ns,
+                change: (bench.median_ns as f64 / baseline_bench.median_ns.max(1) as f64) - 1.0,
+            });
+        }
+    }
+    comparisons
+}
+
+/// Print the comparison of all parts, returns the number of regressions.
+pub fn print_comparisons(baseline_commit: &str, comparisons: &[Comparison], threshold: f64) -> usize {
+    println!("=>> Comparing with {baseline_commit} (regression threshold: +{:.0}%)", threshold * 100.0);
+    let mut num_regressions = 0;
+    for comparison in comparisons {
+        let marker = if comparison.is_regression(threshold) {
+            num_regressions += 1;
+            "❌"
+        } else {
+            "✅"
+        };
+        println!(
+            "{marker} {day_name} {part_name}: {baseline:?} -> {current:?} ({change:+.1}%)",
+            day_name = comparison.day_name,
+            part_name = comparison.part_name,
+            baseline = Duration::from_nanos(comparison.baseline_ns),
+            current = Duration::from_nanos(comparison.current_ns),
+            change = comparison.change * 100.0,
+        );
+    }
+    num_regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part_bench(median_ns: u64) -> PartBench {
+        PartBench { runs: 1, median_ns, min_ns: median_ns, alloc: None }
+    }
+
+    fn results(entries: &[(&str, &str, u64)]) -> BenchResults {
+        let mut results = BenchResults::new();
+        for &(day_name, part_name, median_ns) in entries {
+            results.entry(day_name.to_string())
+                .or_default()
+                .insert(part_name.to_string(), part_bench(median_ns));
+        }
+        results
+    }
+
+    #[test]
+    fn test_part_bench_from_durations() {
+        let durations = [30, 10, 20, 50, 40].map(Duration::from_nanos).to_vec();
+        assert_eq!(PartBench::from_durations(durations), PartBench { runs: 5, median_ns: 30, min_ns: 10, alloc: None });
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let mut history = BenchHistory::default();
+        history.record("abc", &results(&[
+            ("day01", "part1", 100),
+            ("day01", "part2", 100),
+            ("day02", "part1", 100),
+        ]));
+        let current = results(&[
+            ("day01", "part1", 110), // +10%, within threshold
+            ("day01", "part2", 500), // 5x slower
+            ("day02", "part1", 50), // faster
+            ("day03", "part1", 1000), // new day, no baseline
+        ]);
+
+        let comparisons = compare_results(&history.commits["abc"], &current);
+        assert_eq!(comparisons.len(), 3);
+        let regressions: Vec<_> = comparisons.into_iter()
+            .filter(|comparison| comparison.is_regression(0.2))
+            .collect();
+        assert_eq!(regressions, vec![Comparison {
+            day_name: "day01".to_string(),
+            part_name: "part2".to_string(),
+            baseline_ns: 100,
+            current_ns: 500,
+            change: 4.0,
+        }]);
+    }
+
+    #[test]
+    fn test_record_keeps_other_days() {
+        let mut history = BenchHistory::default();
+        history.record("abc", &results(&[("day01", "part1", 100)]));
+        history.record("abc", &results(&[("day02", "part1", 200)]));
+        let days: Vec<_> = history.commits["abc"].days.keys().collect();
+        assert_eq!(days, vec!["day01", "day02"]);
+
+        assert_eq!(history.latest_other_commit("abc"), None);
+        history.record("def", &results(&[("day01", "part1", 100)]));
+        assert_eq!(history.latest_other_commit("abc"), Some("def"));
+    }
+}
@@ -1,31 +1,38 @@
-use nom::{
-    character::{self as chr, complete::newline},
-    combinator::map,
-    multi::separated_list1,
-    IResult
-};
-
-fn parse_input(input: &str) -> Vec<usize> {
-    let line_parser = map(chr::complete::u64, |i| i as usize);
-    let res: IResult<&str, Vec<usize>> = separated_list1(newline, line_parser)(input);
-    let (_, parsed) = res.unwrap();
-    parsed
+use anyhow::Result as AnyResult;
+
+use crate::utils::parsing::{integer, parse_lines};
+use crate::utils::rng::Rng;
+
+pub fn parse_input(input: &str) -> AnyResult<Vec<usize>> {
+    parse_lines(input, integer)
 }
 
-pub fn solve_part1(input: &str) -> Option<usize> {
-    let _parsed = parse_input(input);
+pub fn solve_part1(input: &str) -> AnyResult<Option<usize>> {
+    let _parsed = parse_input(input)?;
 
     // Do something with parsed input
 
-    None
+    Ok(None)
 }
 
-pub fn solve_part2(input: &str) -> Option<usize> {
-    let _parsed = parse_input(input);
+pub fn solve_part2(input: &str) -> AnyResult<Option<usize>> {
+    let _parsed = parse_input(input)?;
 
     // Do something with parsed input
 
-    None
+    Ok(None)
+}
+
+// --------------------------------------------------------
+
+/// Generate `size` lines of random numbers.
+pub fn generate_input(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        input += &rng.gen_range(0..1000).to_string();
+        input.push('\n');
+    }
+    input
 }
 
 #[cfg(test)]
@@ -35,7 +42,7 @@ mod tests {
     #[test]
     fn test_simple_parsing() {
         let input = "123\n456\n789";
-        let parsed = parse_input(input);
+        let parsed = parse_input(input).unwrap();
         dbg!(&parsed);
         assert_eq!(parsed, vec![123_usize, 456_usize, 789_usize]);
     }
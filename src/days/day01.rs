@@ -1,63 +1,215 @@
-use std::collections::HashMap;
-
-use nom::{
-    bytes::complete::tag, character::{self as chr, complete::newline}, multi::separated_list1, sequence::separated_pair, IResult
-};
-use sorted_vec::SortedVec;
-
-fn parse_input(input: &str) -> Vec<(u64, u64)> {
-    let line_parser = separated_pair(chr::complete::u64, tag("   "), chr::complete::u64);
-    let res: IResult<&str, Vec<(u64, u64)>> = separated_list1(newline, line_parser)(input);
-    let (_, parsed) = res.unwrap();
-    parsed
+use anyhow::{Context, Result as AnyResult};
+use nom::{character::complete::space1, sequence::separated_pair};
+
+use crate::utils::counter::Counter;
+use crate::utils::parsing::{integer, parse_lines};
+use crate::utils::rng::Rng;
+
+/// Parse 2 columns of ids (separated by any number of spaces), one pair per line
+pub fn parse_input(input: &str) -> AnyResult<Vec<(u64, u64)>> {
+    parse_lines(input, separated_pair(integer, space1, integer))
 }
 
-pub fn solve_part1(input: &str) -> Option<usize> {
-    let both_lists = parse_input(input);
+pub fn solve_part1(input: &str) -> AnyResult<Option<usize>> {
+    let both_lists = parse_input(input)?;
 
     // Separate in 2 sorted lists
-    let mut left_list: SortedVec<usize> = SortedVec::new();
-    let mut right_list: SortedVec<usize> = SortedVec::new();
-    for (left, right) in both_lists.iter() {
-        left_list.push(*left as usize);
-        right_list.push(*right as usize);
-    }
+    let (mut left_list, mut right_list): (Vec<usize>, Vec<usize>) = both_lists.iter()
+        .map(|&(left, right)| (left as usize, right as usize))
+        .unzip();
+    left_list.sort_unstable();
+    right_list.sort_unstable();
 
     // Now, go through both lists, and sum all their delta
-    let result: usize = left_list.into_vec()
-        .iter()
-        .zip(right_list.into_vec().iter())
-        .map(|(&left, &right)| left.max(right).saturating_sub(left.min(right)))
-        .sum();
+    let result = left_list.iter()
+        .zip(right_list.iter())
+        .try_fold(0usize, |total, (&left, &right)| total.checked_add(left.abs_diff(right)))
+        .context("Total distance overflows")?;
+
+    Ok(Some(result))
+}
+
+pub fn solve_part2(input: &str) -> AnyResult<Option<usize>> {
+    let both_lists = parse_input(input)?;
+
+    // Count each item in both lists
+    let left_ids_counter: Counter<usize> = both_lists.iter().map(|&(left, _)| left as usize).collect();
+    let right_ids_counter: Counter<usize> = both_lists.iter().map(|&(_, right)| right as usize).collect();
+
+    // Compute similarity score based on left list
+    let similarity_score = left_ids_counter
+        .checked_weighted_sum(|id| id.checked_mul(right_ids_counter.count(id)))
+        .context("Similarity score overflows")?;
+
+    Ok(Some(similarity_score))
+}
+
+// --------------------------------------------------------
+
+/// Generate `size` lines of 2 ids, with some right ids taken from the left list.
+pub fn generate_input(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    let mut left_ids = vec![];
+    for _ in 0..size {
+        let left = rng.gen_range(10_000..100_000);
+        left_ids.push(left);
+        let right = if rng.gen_bool(0.3) {
+            *rng.choose(&left_ids)
+        } else {
+            rng.gen_range(10_000..100_000)
+        };
+        input += &format!("{left}   {right}\n");
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Instant;
+
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "\
+3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+    #[test]
+    fn test_parsing() {
+        let parsed = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(parsed, vec![(3, 4), (4, 3), (2, 5), (1, 3), (3, 9), (3, 3)]);
+    }
+
+    #[test]
+    fn test_parsing_windows_input() {
+        let input = EXAMPLE_INPUT.replace('\n', "\r\n");
+        assert_eq!(parse_input(&input).unwrap(), parse_input(EXAMPLE_INPUT).unwrap());
+        assert_eq!(solve_part1(&input).unwrap(), Some(11));
+        // Trailing spaces & blank lines are ignored, not a partial parse
+        assert_eq!(parse_input("3   4  \r\n4   3\r\n\r\n").unwrap(), vec![(3, 4), (4, 3)]);
+        assert_eq!(
+            parse_input("3   4\r\n4   3 x\r\n").unwrap_err().to_string(),
+            "Unparsed leftover at line 2, column 6: \" x\"",
+        );
+    }
+
+    #[test]
+    fn test_example_part1() {
+        assert_eq!(solve_part1(EXAMPLE_INPUT).unwrap(), Some(11));
+    }
+
+    #[test]
+    fn test_example_part2() {
+        assert_eq!(solve_part2(EXAMPLE_INPUT).unwrap(), Some(31));
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let input = "18446744073709551615   0\n18446744073709551615   0\n";
+        assert_eq!(solve_part1(input).unwrap_err().to_string(), "Total distance overflows");
+        let input = "18446744073709551615   18446744073709551615\n18446744073709551615   18446744073709551615\n";
+        assert_eq!(solve_part2(input).unwrap_err().to_string(), "Similarity score overflows");
+    }
+
+    // The previous implementation of part2, re-scanning the right list for each distinct id
+    fn solve_part2_quadratic(input: &str) -> Option<usize> {
+        let both_lists = parse_input(input).unwrap();
+        let right_list: Vec<usize> = both_lists.iter().map(|&(_, right)| right as usize).collect();
+        let mut right_ids_counter = HashMap::new();
+        for right_item in right_list.iter() {
+            if right_ids_counter.contains_key(right_item) {
+                continue // We already have this id's count, skip
+            }
+            let item_count = right_list.iter().filter(|&it| it == right_item).count();
+            right_ids_counter.insert(*right_item, item_count);
+        }
+        let similarity_score = both_lists.iter()
+            .map(|&(left, _)| left as usize * right_ids_counter.get(&(left as usize)).unwrap_or(&0))
+            .sum();
+        Some(similarity_score)
+    }
+
+    #[test]
+    fn test_generated_input() {
+        let input = generate_input(100, &mut Rng::new(42));
+        assert_eq!(input, generate_input(100, &mut Rng::new(42)));
+        assert_eq!(parse_input(&input).unwrap().len(), 100);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with: cargo test --release -- --ignored bench_"]
+    fn bench_part2_counter_vs_quadratic() {
+        let input = generate_input(20_000, &mut Rng::new(42));
+
+        let start = Instant::now();
+        let result = solve_part2(&input).unwrap();
+        let counter_duration = start.elapsed();
 
-    Some(result)
+        let start = Instant::now();
+        let quadratic_result = solve_part2_quadratic(&input);
+        let quadratic_duration = start.elapsed();
+
+        println!("part2 on 20k lines: counter {counter_duration:?}, quadratic {quadratic_duration:?}");
+        assert_eq!(result, quadratic_result);
+        assert!(counter_duration * 10 < quadratic_duration);
+    }
 }
 
-pub fn solve_part2(input: &str) -> Option<usize> {
-    let both_lists = parse_input(input);
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
 
-    // Separate in 2 lists
-    let mut left_list: Vec<usize> = Vec::new();
-    let mut right_list: Vec<usize> = Vec::new();
-    for (left, right) in both_lists.iter() {
-        left_list.push(*left as usize);
-        right_list.push(*right as usize);
+    fn lists_to_input(both_lists: &[(u64, u64)]) -> String {
+        both_lists.iter().map(|(left, right)| format!("{left}   {right}\n")).collect()
     }
 
-    // Count each item in right list
-    let mut right_ids_counter = HashMap::new();
-    for right_item in right_list.iter() {
-        if let Some(_) = right_ids_counter.get(right_item) {
-            continue // We already have this id's count, skip
+    // Pair the smallest of each list, then the 2nd smallest, etc.. one pair at a time.
+    fn solve_part1_reference(both_lists: &[(u64, u64)]) -> usize {
+        let mut left_list: Vec<u64> = both_lists.iter().map(|&(left, _)| left).collect();
+        let mut right_list: Vec<u64> = both_lists.iter().map(|&(_, right)| right).collect();
+        let mut total = 0;
+        while !left_list.is_empty() {
+            let left_idx = (0..left_list.len()).min_by_key(|&idx| left_list[idx]).unwrap();
+            let right_idx = (0..right_list.len()).min_by_key(|&idx| right_list[idx]).unwrap();
+            total += left_list.remove(left_idx).abs_diff(right_list.remove(right_idx)) as usize;
         }
-        let item_count = right_list.iter().filter(|&it| it == right_item).count();
-        right_ids_counter.insert(*right_item, item_count);
+        total
     }
 
-    // Compute similarity score based on left list
-    let similarity_score = left_list.iter()
-        .map(|it| it * right_ids_counter.get(it).unwrap_or(&0))
-        .sum();
+    // Literally what the puzzle says: each left number times how many times it's in the right list
+    fn solve_part2_reference(both_lists: &[(u64, u64)]) -> usize {
+        both_lists.iter()
+            .map(|&(left, _)| {
+                let count = both_lists.iter().filter(|&&(_, right)| right == left).count();
+                left as usize * count
+            })
+            .sum()
+    }
 
-    Some(similarity_score)
+    proptest! {
+        #[test]
+        fn part1_matches_reference(both_lists in prop::collection::vec((0..20u64, 0..20u64), 1..50)) {
+            let input = lists_to_input(&both_lists);
+            prop_assert_eq!(solve_part1(&input).unwrap(), Some(solve_part1_reference(&both_lists)));
+        }
+
+        #[test]
+        fn part2_matches_reference(both_lists in prop::collection::vec((0..20u64, 0..20u64), 1..50)) {
+            let input = lists_to_input(&both_lists);
+            prop_assert_eq!(solve_part2(&input).unwrap(), Some(solve_part2_reference(&both_lists)));
+        }
+
+        #[test]
+        fn part1_is_symmetric(both_lists in prop::collection::vec((0..1000u64, 0..1000u64), 1..50)) {
+            let swapped: Vec<_> = both_lists.iter().map(|&(left, right)| (right, left)).collect();
+            prop_assert_eq!(solve_part1(&lists_to_input(&both_lists)).unwrap(), solve_part1(&lists_to_input(&swapped)).unwrap());
+        }
+    }
 }
@@ -1,79 +1,292 @@
-use nom::{bytes::complete::tag, character::{self as chr, complete::newline}, multi::separated_list1, IResult};
+use std::ops::RangeInclusive;
+
+use anyhow::Result as AnyResult;
+
+use crate::utils::parsing::{integer_list1, parse_lines};
+use crate::utils::rng::Rng;
+use crate::utils::table::Table;
 
 type Report = Vec<i32>;
 
-fn parse_input(input: &str) -> Vec<Report> {
-    let report_parser = separated_list1(tag(" "), chr::complete::i32);
-    let res: IResult<&str, Vec<Report>> = separated_list1(newline, report_parser)(input);
-    let (_, parsed) = res.unwrap();
-    parsed
+/// Parse one report per line, every line must be fully valid and have at least one level.
+///
+/// NOTE: A blank line is an error, not an empty report that would count as safe.
+pub fn parse_input(input: &str) -> AnyResult<Vec<Report>> {
+    parse_lines(input, integer_list1)
 }
 
-fn report_is_safe(report: &Report) -> bool {
-    // Report safety rules:
-    // - The levels are either all increasing or all decreasing.
-    // - Any two adjacent levels differ by at least one and at most three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Increasing,
+    Decreasing,
+}
 
-    let deltas: Vec<i32> = report.windows(2).map(|win| win[1] - win[0]).collect();
+/// Which way the levels of a safe report can go
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllowedDirection {
+    Increasing,
+    Decreasing,
+    Either,
+}
+impl AllowedDirection {
+    fn directions(self) -> &'static [Direction] {
+        match self {
+            AllowedDirection::Increasing => &[Direction::Increasing],
+            AllowedDirection::Decreasing => &[Direction::Decreasing],
+            AllowedDirection::Either => &[Direction::Increasing, Direction::Decreasing],
+        }
+    }
+}
 
-    let first_delta_sign = deltas[0].signum();
-    let all_same_sign = deltas.iter().all(|d| d.signum() == first_delta_sign);
+/// Rules deciding whether a report is safe.
+///
+/// The default is the puzzle's rules (part1):
+/// - The levels are either all increasing or all decreasing.
+/// - Any two adjacent levels differ by at least one and at most three.
+/// - No level can be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyRules {
+    /// Allowed differences between 2 adjacent levels, in the report's direction
+    pub delta_range: RangeInclusive<i64>,
+    pub direction: AllowedDirection,
+    /// Max number of levels that can be removed to make a report safe
+    pub max_removals: usize,
+}
+impl Default for SafetyRules {
+    fn default() -> Self {
+        Self {
+            delta_range: 1..=3,
+            direction: AllowedDirection::Either,
+            max_removals: 0,
+        }
+    }
+}
+impl SafetyRules {
+    /// Rules of part2, where the "Problem Dampener" can remove 1 level
+    pub fn with_problem_dampener() -> Self {
+        Self { max_removals: 1, ..Self::default() }
+    }
 
-    let delta_within_acceptable_range = deltas.iter().all(|d| {
-        let abs = d.abs();
-        1 <= abs && abs <= 3
-    });
+    fn is_safe_step(&self, from: i32, to: i32, direction: Direction) -> bool {
+        // NOTE: Deltas of extreme levels don't fit in an i32
+        let delta = to as i64 - from as i64;
+        let delta = match direction {
+            Direction::Increasing => delta,
+            Direction::Decreasing => -delta,
+        };
+        self.delta_range.contains(&delta)
+    }
 
-    // println!("For report: {report:?} (deltas: {deltas:?}) :: same sign? {all_same_sign} in_range? {delta_within_acceptable_range}");
+    /// Indices of the first 2 adjacent (non-removed) levels breaking the rules for the direction
+    fn first_unsafe_step(&self, report: &[i32], direction: Direction, removed: Option<&Removed>) -> Option<(usize, usize)> {
+        let mut kept_indices = (0..report.len()).filter(|&idx| !is_removed(removed, idx));
+        let mut prev_idx = kept_indices.next()?;
+        for idx in kept_indices {
+            if !self.is_safe_step(report[prev_idx], report[idx], direction) {
+                return Some((prev_idx, idx));
+            }
+            prev_idx = idx;
+        }
+        None
+    }
 
-    all_same_sign && delta_within_acceptable_range
-}
+    fn is_safe_in_direction(&self, report: &[i32], direction: Direction, removed: Option<&Removed>, max_removals: usize) -> bool {
+        let Some((from_idx, to_idx)) = self.first_unsafe_step(report, direction, removed) else {
+            return true;
+        };
+        if max_removals == 0 {
+            return false;
+        }
+        // Both levels of the unsafe step can't be kept (nothing can come back between them), so one
+        // of them must be removed, try both.
+        [from_idx, to_idx].into_iter().any(|idx| {
+            let removed = Removed { idx, previous: removed };
+            self.is_safe_in_direction(report, direction, Some(&removed), max_removals - 1)
+        })
+    }
 
-pub fn solve_part1(input: &str) -> Option<usize> {
-    let reports = parse_input(input);
+    /// Whether the report is safe, after removing at most `max_removals` levels.
+    ///
+    /// For a given direction, the first unsafe step tells us that one of its 2 levels must go, so
+    /// we only explore these 2 removals (recursively), without allocating anything.
+    /// That's O(2^k * n) per report, linear for a given `max_removals`, instead of trying to remove
+    /// every level in turn.
+    ///
+    /// NOTE: Reports with less than 2 levels have no adjacent levels to break the rules, so they
+    ///   are safe.
+    pub fn is_safe(&self, report: &[i32]) -> bool {
+        self.direction.directions()
+            .iter()
+            .any(|&direction| self.is_safe_in_direction(report, direction, None, self.max_removals))
+    }
+}
 
-    let num_safe_reports = reports.iter()
-        .filter(|r| report_is_safe(r))
-        .count();
+/// First rule broken by a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Levels `idx` & `idx + 1` don't go in the report's direction
+    SignFlip { idx: usize, delta: i64 },
+    /// Levels `idx` & `idx + 1` differ too little or too much
+    DeltaOutOfRange { idx: usize, delta: i64 },
+}
 
-    Some(num_safe_reports)
+/// Why a report is unsafe, and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportExplanation {
+    /// `None` when the report is safe (without removing anything)
+    pub violation: Option<Violation>,
+    /// Indices of the levels that make an unsafe report safe when removed (alone)
+    pub fixing_removals: Vec<usize>,
 }
 
-// --------------------------------------------------------
+impl SafetyRules {
+    /// Explain why a report is unsafe without any removal, and which single removals fix it.
+    ///
+    /// The report's direction is the one allowed by the rules, or (if either is allowed) the one it
+    /// is safe in, else the one of its first non-flat step.
+    pub fn explain(&self, report: &[i32]) -> ReportExplanation {
+        let deltas = report.windows(2).map(|win| win[1] as i64 - win[0] as i64);
+        let direction = match self.direction {
+            AllowedDirection::Increasing => Direction::Increasing,
+            AllowedDirection::Decreasing => Direction::Decreasing,
+            AllowedDirection::Either => {
+                let safe_direction = [Direction::Increasing, Direction::Decreasing]
+                    .into_iter()
+                    .find(|&direction| self.first_unsafe_step(report, direction, None).is_none());
+                safe_direction.unwrap_or_else(|| {
+                    let first_non_flat = deltas.clone().find(|&delta| delta != 0).unwrap_or(0);
+                    if first_non_flat < 0 { Direction::Decreasing } else { Direction::Increasing }
+                })
+            }
+        };
+        let violation = deltas.enumerate().find_map(|(idx, delta)| {
+            let directed_delta = match direction {
+                Direction::Increasing => delta,
+                Direction::Decreasing => -delta,
+            };
+            if self.delta_range.contains(&directed_delta) {
+                None
+            } else if directed_delta < 0 && *self.delta_range.start() >= 0 {
+                // Only a flip when the rules don't allow any step back
+                Some(Violation::SignFlip { idx, delta })
+            } else {
+                Some(Violation::DeltaOutOfRange { idx, delta })
+            }
+        });
 
-// Copied from https://stackoverflow.com/a/79061120/5655255
-fn skip_nth<I: Iterator>(iter: I, n: usize) -> impl Iterator<Item = I::Item> {
-    iter.enumerate().filter_map(move |(idx, item)| {
-        if idx == n {
-            None // Skip the N-th element
-        } else {
-            Some(item) // Keep all other elements
-        }
-    })
+        let fixing_removals = match violation {
+            None => vec![],
+            Some(_) => (0..report.len())
+                .filter(|&idx| {
+                    let removed = Removed { idx, previous: None };
+                    self.direction.directions()
+                        .iter()
+                        .any(|&direction| self.is_safe_in_direction(report, direction, Some(&removed), 0))
+                })
+                .collect(),
+        };
+        ReportExplanation { violation, fixing_removals }
+    }
+}
+
+/// Indices of levels removed from a report.
+///
+/// NOTE: It's a linked list living on the stack of the recursive calls exploring removals,
+///   so trying removals doesn't need any allocation.
+struct Removed<'a> {
+    idx: usize,
+    previous: Option<&'a Removed<'a>>,
 }
 
-fn can_fix_report(report: &Report) -> bool {
-    for skip_idx in 0..report.len() {
-        let maybe_fixed_report: Report = skip_nth(report.iter(), skip_idx).cloned().collect();
-        if report_is_safe(&maybe_fixed_report) {
-            return true
+fn is_removed(removed: Option<&Removed>, idx: usize) -> bool {
+    let mut current = removed;
+    while let Some(node) = current {
+        if node.idx == idx {
+            return true;
         }
+        current = node.previous;
     }
     false
 }
 
-pub fn solve_part2(input: &str) -> Option<usize> {
-    let reports = parse_input(input);
+pub fn count_safe_reports(reports: &[Report], rules: &SafetyRules) -> usize {
+    reports.iter()
+        .filter(|report| rules.is_safe(report))
+        .count()
+}
+
+pub fn solve_part1(input: &str) -> AnyResult<Option<usize>> {
+    let reports = parse_input(input)?;
+    Ok(Some(count_safe_reports(&reports, &SafetyRules::default())))
+}
+
+pub fn solve_part2(input: &str) -> AnyResult<Option<usize>> {
+    let reports = parse_input(input)?;
+    Ok(Some(count_safe_reports(&reports, &SafetyRules::with_problem_dampener())))
+}
+
+/// Table of all reports with their safety status, first problem & fixes (using puzzle rules).
+/// Levels are numbered from 1 in each report, like lines.
+pub fn explain_input(input: &str, _colored: bool) -> AnyResult<String> {
+    let reports = parse_input(input)?;
+    let rules = SafetyRules::default();
+    let mut table = Table::new(&["Line", "Report", "Status", "First problem", "Fixed by removing"]);
+    for (line_idx, report) in reports.iter().enumerate() {
+        let explanation = rules.explain(report);
+        let status = match (&explanation.violation, explanation.fixing_removals.is_empty()) {
+            (None, _) => "safe",
+            (Some(_), false) => "fixable",
+            (Some(_), true) => "unsafe",
+        };
+        let problem = match explanation.violation {
+            None => String::new(),
+            Some(Violation::SignFlip { idx, delta }) => {
+                format!("levels #{num}→#{next}: {delta:+} flips direction", num = idx + 1, next = idx + 2)
+            }
+            Some(Violation::DeltaOutOfRange { idx, delta }) => {
+                format!("levels #{num}→#{next}: {delta:+} out of {range:?}", num = idx + 1, next = idx + 2, range = rules.delta_range)
+            }
+        };
+        let fixes: Vec<String> = explanation.fixing_removals.iter()
+            .map(|&idx| format!("#{num} ({level})", num = idx + 1, level = report[idx]))
+            .collect();
+        let levels: Vec<String> = report.iter().map(|level| level.to_string()).collect();
+        table.add_row(vec![
+            (line_idx + 1).to_string(),
+            levels.join(" "),
+            status.to_string(),
+            problem,
+            fixes.join(", "),
+        ]);
+    }
+    Ok(table.to_string())
+}
 
-    let num_safe_reports = reports.iter()
-        .filter(|r| report_is_safe(r))
-        .count();
-    let num_fixed_reports = reports.iter()
-        .filter(|r| !report_is_safe(r))
-        .filter(|r| can_fix_report(r))
-        .count();
+// --------------------------------------------------------
 
-    Some(num_safe_reports + num_fixed_reports)
+/// Generate `size` reports, a mix of safe, fixable & unsafe ones.
+pub fn generate_input(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let num_levels = rng.gen_range(5..9) as usize;
+        let direction = if rng.gen_bool(0.5) { 1 } else { -1 };
+        let mut level = rng.gen_range(30..70) as i32;
+        let mut report: Report = vec![];
+        for _ in 0..num_levels {
+            report.push(level);
+            level += direction * rng.gen_range(1..4) as i32;
+        }
+        // Break some levels (once to be fixable, more to be unsafe)
+        while rng.gen_bool(0.3) {
+            let idx = rng.gen_index(report.len());
+            report[idx] = rng.gen_range(1..100) as i32;
+        }
+        let levels: Vec<_> = report.iter().map(|level| level.to_string()).collect();
+        input += &levels.join(" ");
+        input += "\n";
+    }
+    input
 }
 
 // --------------------------------------------------------
@@ -82,6 +295,15 @@ pub fn solve_part2(input: &str) -> Option<usize> {
 mod tests {
     use super::*;
 
+    fn report_is_safe(report: &[i32]) -> bool {
+        SafetyRules::default().is_safe(report)
+    }
+
+    // Safe with the puzzle's rules, after removing at most `max_removals` levels
+    pub(super) fn is_safe_with_removals(report: &[i32], max_removals: usize) -> bool {
+        SafetyRules { max_removals, ..SafetyRules::default() }.is_safe(report)
+    }
+
     const EXAMPLE_INPUT: &str = "\
 7 6 4 2 1
 1 2 7 8 9
@@ -93,7 +315,7 @@ mod tests {
 
     #[test]
     fn test_parsing() {
-        let parsed = parse_input(EXAMPLE_INPUT);
+        let parsed = parse_input(EXAMPLE_INPUT).unwrap();
         dbg!(&parsed);
         assert_eq!(parsed, vec![
             vec![7, 6, 4, 2, 1],
@@ -105,15 +327,63 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_parsing_invalid_lines() {
+        assert_eq!(
+            parse_input("1 2 3\n4 5 x 6\n7 8\n").unwrap_err().to_string(),
+            "Unparsed leftover at line 2, column 5: \"x 6\"",
+        );
+        assert!(parse_input("1 2 3\n99999999999 1\n").is_err());
+        // Levels can be separated by any spaces
+        assert_eq!(parse_input("1 2 3 \n4  5\t6\n").unwrap(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_parsing_windows_input() {
+        let input = EXAMPLE_INPUT.replace('\n', " \r\n");
+        assert_eq!(parse_input(&input).unwrap(), parse_input(EXAMPLE_INPUT).unwrap());
+        assert_eq!(solve_part2(&input).unwrap(), Some(4));
+        assert_eq!(
+            parse_input("1 2 3\r\n4 5\r6\r\n").unwrap_err().to_string(),
+            "Unparsed leftover at line 2, column 4: \"\\r6\"",
+        );
+    }
+
+    #[test]
+    fn test_degenerate_reports() {
+        // A blank line isn't an empty (so safe) report
+        let input = "7 6 4 2 1\n\n1 2 7 8 9\n";
+        for result in [parse_input(input).map(|_| None), solve_part1(input), solve_part2(input)] {
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "Failed to parse input at line 2, column 1: expected a number near: \"\"",
+            );
+        }
+        // Reports with 1 level are safe, in both parts
+        let input = "5\n1 2\n";
+        assert_eq!(solve_part1(input).unwrap(), Some(2));
+        assert_eq!(solve_part2(input).unwrap(), Some(2));
+        // A report fixed by removing a level down to a single level is safe too
+        assert_eq!(solve_part1("1 9\n").unwrap(), Some(0));
+        assert_eq!(solve_part2("1 9\n").unwrap(), Some(1));
+        assert_eq!(parse_input("").unwrap(), Vec::<Report>::new());
+        assert_eq!(solve_part1("").unwrap(), Some(0));
+        for rules in [SafetyRules::default(), SafetyRules::with_problem_dampener()] {
+            assert!(rules.is_safe(&[]));
+            assert!(rules.is_safe(&[i32::MIN]));
+            assert_eq!(rules.explain(&[]), ReportExplanation { violation: None, fixing_removals: vec![] });
+        }
+    }
+
     #[test]
     fn test_example_part1() {
-        let res = solve_part1(EXAMPLE_INPUT);
+        let res = solve_part1(EXAMPLE_INPUT).unwrap();
         assert_eq!(res, Some(2));
     }
 
     #[test]
     fn test_example_part2() {
-        let res = solve_part2(EXAMPLE_INPUT);
+        let res = solve_part2(EXAMPLE_INPUT).unwrap();
         assert_eq!(res, Some(4));
     }
 
@@ -131,15 +401,198 @@ mod tests {
         assert!(!report_is_safe(&unsafe_report2));
     }
 
+    #[test]
+    fn test_report_safety_edge_cases() {
+        assert!(report_is_safe(&[42]));
+        // Deltas don't fit in an i32
+        assert!(!report_is_safe(&[i32::MIN, i32::MAX]));
+        assert!(!report_is_safe(&[i32::MAX, i32::MIN]));
+    }
+
+    // Copied from https://stackoverflow.com/a/79061120/5655255
+    fn skip_nth<I: Iterator>(iter: I, n: usize) -> impl Iterator<Item = I::Item> {
+        iter.enumerate().filter_map(move |(idx, item)| {
+            if idx == n {
+                None // Skip the N-th element
+            } else {
+                Some(item) // Keep all other elements
+            }
+        })
+    }
+
+    // The previous (brute force) implementation, removing each level in turn
+    fn can_fix_report(report: &Report) -> bool {
+        for skip_idx in 0..report.len() {
+            let maybe_fixed_report: Report = skip_nth(report.iter(), skip_idx).cloned().collect();
+            if report_is_safe(&maybe_fixed_report) {
+                return true
+            }
+        }
+        false
+    }
+
     #[test]
     fn test_fixable_report() {
         // Can remove a `4` to make it safe
         let fixable_report: Report = vec![8, 6, 4, 4, 1];
-        assert!(can_fix_report(&fixable_report));
+        assert!(is_safe_with_removals(&fixable_report, 1));
+        assert!(!is_safe_with_removals(&fixable_report, 0));
 
         // Cannot be fixed, delta jump is too high
         let unfixable_report: Report = vec![1, 2, 7, 8, 9];
-        assert!(!can_fix_report(&unfixable_report));
+        assert!(!is_safe_with_removals(&unfixable_report, 1));
+    }
+
+    #[test]
+    fn test_safe_with_more_removals() {
+        // `10 11` must both go
+        let report: Report = vec![1, 2, 3, 10, 11, 4, 5, 6];
+        assert!(!is_safe_with_removals(&report, 1));
+        assert!(is_safe_with_removals(&report, 2));
+
+        // Removing the first level fixes the direction (that's the tricky case for 1 removal)
+        let report: Report = vec![5, 1, 2, 3, 4];
+        assert!(is_safe_with_removals(&report, 1));
+
+        // With enough removals, any report is safe (a single level is)
+        let report: Report = vec![1, 50, 20, 80];
+        assert!(!is_safe_with_removals(&report, 2));
+        assert!(is_safe_with_removals(&report, 3));
+    }
+
+    #[test]
+    fn test_rules_direction() {
+        let increasing_only = SafetyRules { direction: AllowedDirection::Increasing, ..SafetyRules::default() };
+        let decreasing_only = SafetyRules { direction: AllowedDirection::Decreasing, ..SafetyRules::default() };
+        assert!(increasing_only.is_safe(&[1, 3, 6, 7, 9]));
+        assert!(!increasing_only.is_safe(&[7, 6, 4, 2, 1]));
+        assert!(decreasing_only.is_safe(&[7, 6, 4, 2, 1]));
+        assert!(!decreasing_only.is_safe(&[1, 3, 6, 7, 9]));
+        // In the example: `7 6 4 2 1` is the only safe decreasing report, `1 3 6 7 9` the only increasing one
+        let reports = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(count_safe_reports(&reports, &increasing_only), 1);
+        assert_eq!(count_safe_reports(&reports, &decreasing_only), 1);
+    }
+
+    #[test]
+    fn test_rules_delta_range() {
+        let reports = parse_input(EXAMPLE_INPUT).unwrap();
+
+        // Allow plateaus: `8 6 4 4 1` becomes safe
+        let with_plateaus = SafetyRules { delta_range: 0..=3, ..SafetyRules::default() };
+        assert!(with_plateaus.is_safe(&[8, 6, 4, 4, 1]));
+        assert_eq!(count_safe_reports(&reports, &with_plateaus), 3);
+
+        // Allow bigger jumps: `1 2 7 8 9` & `9 7 6 2 1` become safe
+        let with_jumps = SafetyRules { delta_range: 1..=5, ..SafetyRules::default() };
+        assert_eq!(count_safe_reports(&reports, &with_jumps), 4);
+
+        // Only exact steps of 2
+        let steps_of_2 = SafetyRules { delta_range: 2..=2, ..SafetyRules::default() };
+        assert!(steps_of_2.is_safe(&[8, 6, 4, 2]));
+        assert!(!steps_of_2.is_safe(&[7, 6, 4, 2, 1]));
+        assert_eq!(count_safe_reports(&reports, &steps_of_2), 0);
+    }
+
+    #[test]
+    fn test_rules_max_removals() {
+        let reports = parse_input(EXAMPLE_INPUT).unwrap();
+        assert_eq!(count_safe_reports(&reports, &SafetyRules::default()), 2);
+        assert_eq!(count_safe_reports(&reports, &SafetyRules::with_problem_dampener()), 4);
+        // `1 2 7 8 9` & `9 7 6 2 1` can be fixed by removing 2 levels (`1 2` & `2 1`)
+        let with_2_removals = SafetyRules { max_removals: 2, ..SafetyRules::default() };
+        assert_eq!(count_safe_reports(&reports, &with_2_removals), 6);
+
+        // Removals combine with the other rules
+        let increasing_with_dampener = SafetyRules {
+            direction: AllowedDirection::Increasing,
+            ..SafetyRules::with_problem_dampener()
+        };
+        assert!(increasing_with_dampener.is_safe(&[5, 1, 2, 3, 4]));
+        assert!(!increasing_with_dampener.is_safe(&[5, 4, 3, 2, 1]));
+    }
+
+    #[test]
+    fn test_explain() {
+        let rules = SafetyRules::default();
+        assert_eq!(rules.explain(&[7, 6, 4, 2, 1]), ReportExplanation { violation: None, fixing_removals: vec![] });
+        assert_eq!(rules.explain(&[1, 2, 7, 8, 9]), ReportExplanation {
+            violation: Some(Violation::DeltaOutOfRange { idx: 1, delta: 5 }),
+            fixing_removals: vec![],
+        });
+        assert_eq!(rules.explain(&[1, 3, 2, 4, 5]), ReportExplanation {
+            violation: Some(Violation::SignFlip { idx: 1, delta: -1 }),
+            fixing_removals: vec![1, 2],
+        });
+        assert_eq!(rules.explain(&[8, 6, 4, 4, 1]), ReportExplanation {
+            violation: Some(Violation::DeltaOutOfRange { idx: 2, delta: 0 }),
+            fixing_removals: vec![2, 3],
+        });
+        // The direction is given by the first step, so the sign flip is the 2nd step even if
+        // removing the first level is the fix
+        assert_eq!(rules.explain(&[3, 1, 2, 3, 4]), ReportExplanation {
+            violation: Some(Violation::SignFlip { idx: 1, delta: 1 }),
+            fixing_removals: vec![0],
+        });
+    }
+
+    #[test]
+    fn test_explain_with_steps_back() {
+        // Steps back of 1 are allowed, bigger ones are out of range (not a sign flip)
+        let rules = SafetyRules { delta_range: -1..=3, ..SafetyRules::default() };
+        assert!(rules.is_safe(&[1, 3, 2, 4]));
+        assert_eq!(rules.explain(&[1, 3, 2, 4]), ReportExplanation { violation: None, fixing_removals: vec![] });
+        assert_eq!(rules.explain(&[1, 3, 1, 4]), ReportExplanation {
+            violation: Some(Violation::DeltaOutOfRange { idx: 1, delta: -2 }),
+            fixing_removals: vec![1, 2],
+        });
+        // Only safe decreasing, even if the first step goes up
+        assert!(rules.is_safe(&[4, 5, 2, 1]));
+        assert_eq!(rules.explain(&[4, 5, 2, 1]).violation, None);
+    }
+
+    #[test]
+    fn test_explain_input() {
+        let table = explain_input(EXAMPLE_INPUT, false).unwrap();
+        assert_eq!(table, "\
+Line | Report    | Status  | First problem                    | Fixed by removing
+-----+-----------+---------+----------------------------------+------------------
+1    | 7 6 4 2 1 | safe    |                                  |
+2    | 1 2 7 8 9 | unsafe  | levels #2→#3: +5 out of 1..=3    |
+3    | 9 7 6 2 1 | unsafe  | levels #3→#4: -4 out of 1..=3    |
+4    | 1 3 2 4 5 | fixable | levels #2→#3: -1 flips direction | #2 (3), #3 (2)
+5    | 8 6 4 4 1 | fixable | levels #3→#4: +0 out of 1..=3    | #3 (4), #4 (4)
+6    | 1 3 6 7 9 | safe    |                                  |
+");
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_safe_with_removals_doesnt_allocate() {
+        let report: Report = vec![1, 2, 3, 10, 11, 4, 5, 6, 42, 7];
+        let (is_safe, stats) = crate::alloc_stats::measure(|| is_safe_with_removals(&report, 3));
+        assert!(is_safe);
+        assert_eq!(stats.unwrap().allocations, 0);
+    }
+
+    #[test]
+    fn test_fixable_matches_brute_force_on_generated_input() {
+        let input = generate_input(1000, &mut Rng::new(42));
+        for report in parse_input(&input).unwrap() {
+            assert_eq!(is_safe_with_removals(&report, 1), can_fix_report(&report), "report: {report:?}");
+        }
+    }
+
+    #[test]
+    fn test_generated_input() {
+        let input = generate_input(100, &mut Rng::new(42));
+        assert_eq!(input, generate_input(100, &mut Rng::new(42)));
+        let reports = parse_input(&input).unwrap();
+        assert_eq!(reports.len(), 100);
+        // Should have all kinds of reports
+        assert!(reports.iter().any(|r| report_is_safe(r)));
+        assert!(reports.iter().any(|r| !report_is_safe(r) && is_safe_with_removals(r, 1)));
+        assert!(reports.iter().any(|r| !is_safe_with_removals(r, 1)));
     }
 
     #[test]
@@ -149,3 +602,133 @@ mod tests {
         assert_eq!(updated, vec!["zero", "one", "three"]); // "two" has been skipped
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use super::tests::is_safe_with_removals;
+    use crate::utils::strategies;
+
+    // Literally the puzzle rules, checked separately for both directions
+    fn report_is_safe_reference(report: &[i32]) -> bool {
+        let increasing = report.windows(2).all(|win| win[0] < win[1] && win[1] - win[0] <= 3);
+        let decreasing = report.windows(2).all(|win| win[0] > win[1] && win[0] - win[1] <= 3);
+        increasing || decreasing
+    }
+
+    fn can_fix_report_reference(report: &[i32]) -> bool {
+        (0..report.len()).any(|skip_idx| {
+            let mut fixed_report = report.to_vec();
+            fixed_report.remove(skip_idx);
+            report_is_safe_reference(&fixed_report)
+        })
+    }
+
+    // Try all ways to remove up to `max_removals` levels
+    fn is_safe_with_removals_reference(report: &[i32], max_removals: usize) -> bool {
+        report_is_safe_reference(report) || (max_removals > 0 && (0..report.len()).any(|skip_idx| {
+            let mut fixed_report = report.to_vec();
+            fixed_report.remove(skip_idx);
+            is_safe_with_removals_reference(&fixed_report, max_removals - 1)
+        }))
+    }
+
+    // Same, for any rules
+    fn rules_are_satisfied_reference(rules: &SafetyRules, report: &[i32]) -> bool {
+        let deltas: Vec<i64> = report.windows(2).map(|win| win[1] as i64 - win[0] as i64).collect();
+        let increasing = deltas.iter().all(|delta| rules.delta_range.contains(delta));
+        let decreasing = deltas.iter().all(|delta| rules.delta_range.contains(&-delta));
+        let direction_ok = match rules.direction {
+            AllowedDirection::Increasing => increasing,
+            AllowedDirection::Decreasing => decreasing,
+            AllowedDirection::Either => increasing || decreasing,
+        };
+        direction_ok || (rules.max_removals > 0 && (0..report.len()).any(|skip_idx| {
+            let mut fixed_report = report.to_vec();
+            fixed_report.remove(skip_idx);
+            let rules = SafetyRules { max_removals: rules.max_removals - 1, ..rules.clone() };
+            rules_are_satisfied_reference(&rules, &fixed_report)
+        }))
+    }
+
+    fn safety_rules() -> impl Strategy<Value = SafetyRules> {
+        let direction = prop_oneof![
+            Just(AllowedDirection::Increasing),
+            Just(AllowedDirection::Decreasing),
+            Just(AllowedDirection::Either),
+        ];
+        // NOTE: Ranges can allow steps back, going against the report's direction
+        (-3..3i64, 0..4i64, direction, 0..3usize).prop_map(|(min_delta, delta_width, direction, max_removals)| {
+            SafetyRules { delta_range: min_delta..=min_delta + delta_width, direction, max_removals }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn safety_matches_reference(report in strategies::report(2, 10)) {
+            prop_assert_eq!(SafetyRules::default().is_safe(&report), report_is_safe_reference(&report));
+        }
+
+        #[test]
+        fn fixable_matches_reference(report in strategies::report(3, 10)) {
+            prop_assert_eq!(is_safe_with_removals(&report, 1), can_fix_report_reference(&report));
+        }
+
+        #[test]
+        fn safe_with_removals_matches_reference(report in strategies::report(0, 8), max_removals in 0..4usize) {
+            prop_assert_eq!(
+                is_safe_with_removals(&report, max_removals),
+                is_safe_with_removals_reference(&report, max_removals),
+            );
+        }
+
+        #[test]
+        fn any_rules_match_reference(rules in safety_rules(), report in strategies::report(0, 8)) {
+            prop_assert_eq!(rules.is_safe(&report), rules_are_satisfied_reference(&rules, &report));
+        }
+
+        #[test]
+        fn explanation_matches_safety(rules in safety_rules(), report in strategies::report(0, 8)) {
+            let rules = SafetyRules { max_removals: 0, ..rules };
+            let explanation = rules.explain(&report);
+            prop_assert_eq!(explanation.violation.is_none(), rules.is_safe(&report));
+            let with_dampener = SafetyRules { max_removals: 1, ..rules.clone() };
+            prop_assert_eq!(
+                explanation.violation.is_none() || !explanation.fixing_removals.is_empty(),
+                with_dampener.is_safe(&report),
+            );
+        }
+
+        #[test]
+        fn safe_reports_are_fixable(report in strategies::report(3, 10)) {
+            prop_assert!(!SafetyRules::default().is_safe(&report) || is_safe_with_removals(&report, 1));
+        }
+
+        #[test]
+        fn safety_is_kept_when_reversed_or_shifted(report in strategies::report(2, 10), shift in -50..50i32) {
+            let is_safe = SafetyRules::default().is_safe(&report);
+            let reversed: Report = report.iter().rev().copied().collect();
+            let shifted: Report = report.iter().map(|level| level + shift).collect();
+            prop_assert_eq!(SafetyRules::default().is_safe(&reversed), is_safe);
+            prop_assert_eq!(SafetyRules::default().is_safe(&shifted), is_safe);
+        }
+
+        #[test]
+        fn part2_counts_at_least_part1(reports in prop::collection::vec(strategies::report(3, 10), 1..20)) {
+            let input: String = reports.iter()
+                .map(|report| {
+                    let levels: Vec<_> = report.iter().map(|level| level.to_string()).collect();
+                    levels.join(" ") + "\n"
+                })
+                .collect();
+            let num_safe = reports.iter().filter(|r| report_is_safe_reference(r)).count();
+            let num_safe_or_fixable = reports.iter()
+                .filter(|r| report_is_safe_reference(r) || can_fix_report_reference(r))
+                .count();
+            prop_assert_eq!(solve_part1(&input).unwrap(), Some(num_safe));
+            prop_assert_eq!(solve_part2(&input).unwrap(), Some(num_safe_or_fixable));
+        }
+    }
+}
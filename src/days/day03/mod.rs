@@ -0,0 +1,819 @@
+mod stream;
+mod vm;
+
+use std::ops::Range;
+
+use anyhow::{Context, Result as AnyResult};
+use nom::{
+    branch::alt, bytes::complete::tag, character::complete::{char, digit1}, combinator::{map, map_opt}, sequence::{delimited, separated_pair}, IResult
+};
+
+use crate::utils::rng::Rng;
+use crate::utils::table::Table;
+pub use stream::evaluate_reader;
+use vm::{Instruction, Machine, TraceStep, Vm};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ast {
+    MulNode(u64, u64),
+    Do,
+    Dont,
+}
+
+/// Where an instruction was found in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// In bytes, from the start of the input
+    pub offset: usize,
+    /// In bytes
+    pub len: usize,
+}
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// An instruction, with where it was found in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Node {
+    pub ast: Ast,
+    pub span: Span,
+}
+
+impl Instruction for Ast {
+    fn execute(&self, machine: &mut Machine) -> AnyResult<()> {
+        match *self {
+            Ast::MulNode(n1, n2) => {
+                if machine.muls_enabled {
+                    let product = n1.checked_mul(n2)
+                        .and_then(|product| usize::try_from(product).ok())
+                        .context("Product overflows")?;
+                    machine.add_to_total(product)?;
+                }
+            }
+            Ast::Do => machine.muls_enabled = true,
+            Ast::Dont => machine.muls_enabled = false,
+        }
+        Ok(())
+    }
+}
+
+/// What to do with a `mul` operand too big for a `u32`
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The `mul` is invalid
+    Reject,
+    /// The operand is clamped to `u32::MAX`
+    Saturate,
+    /// The operand is kept as is, up to `u64::MAX` (the `mul` is invalid above)
+    Wide,
+}
+
+/// How `mul` operands are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperandRules {
+    /// Digits allowed in an operand (leading zeros included)
+    pub min_digits: usize,
+    pub max_digits: usize,
+    pub overflow: Overflow,
+}
+impl Default for OperandRules {
+    /// The puzzle's rules: numbers of 1 to 3 digits
+    fn default() -> Self {
+        Self { min_digits: 1, max_digits: 3, overflow: Overflow::Reject }
+    }
+}
+impl OperandRules {
+    /// Any number of digits, with the given overflow behaviour
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn unlimited_digits(overflow: Overflow) -> Self {
+        Self { min_digits: 1, max_digits: usize::MAX, overflow }
+    }
+
+    /// The value of an operand made of these digits, if the rules allow it (without the digit
+    /// limits, see `parse_operand`)
+    fn operand_value(&self, digits: &str) -> Option<u64> {
+        let value = digits.bytes()
+            .try_fold(0u64, |value, digit| value.checked_mul(10)?.checked_add((digit - b'0') as u64));
+        let u32_max = u32::MAX as u64;
+        match self.overflow {
+            Overflow::Reject => value.filter(|&value| value <= u32_max),
+            Overflow::Saturate => Some(value.map_or(u32_max, |value| value.min(u32_max))),
+            Overflow::Wide => value,
+        }
+    }
+
+    /// Whether more digits after these could make a valid operand
+    fn is_operand_prefix(&self, digits: &str) -> bool {
+        digits.bytes().all(|byte| byte.is_ascii_digit())
+            && digits.len() <= self.max_digits
+            && (digits.is_empty() || self.operand_value(digits).is_some())
+    }
+}
+
+fn parse_operand(operands: OperandRules) -> impl Fn(&str) -> IResult<&str, u64> {
+    move |input| {
+        map_opt(digit1, |digits: &str| {
+            if !(operands.min_digits..=operands.max_digits).contains(&digits.len()) {
+                return None;
+            }
+            operands.operand_value(digits)
+        })(input)
+    }
+}
+
+// Format: `mul(123,42)`
+fn parse_mul(operands: OperandRules) -> impl Fn(&str) -> IResult<&str, Ast> {
+    move |input| {
+        let (input, _) = tag("mul")(input)?;
+        let (input, (n1, n2)) = delimited(
+            char('('),
+            separated_pair(parse_operand(operands), tag(","), parse_operand(operands)),
+            char(')'),
+        )(input)?;
+        Ok((input, Ast::MulNode(n1, n2)))
+    }
+}
+
+pub fn solve_part1(input: &str) -> AnyResult<Option<usize>> {
+    let total = evaluate(input, InstructionSet::Mul, OperandRules::default())?;
+    Ok(Some(total))
+}
+
+// ----------------------------------------------------
+
+fn parse_do(input: &str) -> IResult<&str, Ast> {
+    map(tag("do()"), |_| Ast::Do)(input)
+}
+
+fn parse_dont(input: &str) -> IResult<&str, Ast> {
+    map(tag("don't()"), |_| Ast::Dont)(input)
+}
+
+fn parse_ast(operands: OperandRules) -> impl Fn(&str) -> IResult<&str, Ast> {
+    move |input| {
+        alt((
+            parse_mul(operands),
+            parse_dont, // NOTE: must be before `do` to work
+            parse_do,
+        ))(input)
+    }
+}
+
+pub fn parse_input(input: &str) -> AnyResult<Vec<Node>> {
+    InstructionSet::MulWithConditionals.parse(input)
+}
+
+/// Instructions recognized in the corrupted memory, everything else is gibberish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionSet {
+    /// Part1: only `mul`
+    Mul,
+    /// Part2: `mul`, with `do` & `don't` to enable/disable them
+    MulWithConditionals,
+}
+impl InstructionSet {
+    /// Whether the byte can start an instruction of the set
+    fn is_start_byte(self, byte: u8) -> bool {
+        match self {
+            InstructionSet::Mul => byte == b'm',
+            InstructionSet::MulWithConditionals => byte == b'm' || byte == b'd',
+        }
+    }
+
+    /// Parse an instruction of the set at the start of the input
+    fn parse_instruction(self, input: &str, operands: OperandRules) -> IResult<&str, Ast> {
+        match self {
+            InstructionSet::Mul => parse_mul(operands)(input),
+            InstructionSet::MulWithConditionals => parse_ast(operands)(input),
+        }
+    }
+
+    /// Whether more bytes after the input could make it a valid instruction of the set.
+    ///
+    /// NOTE: Without a digit limit, operands can have any number of leading zeros, so a prefix can
+    ///   be arbitrarily long, but it's only that long when the memory is made to be.
+    fn is_instruction_prefix(self, input: &str, operands: OperandRules) -> bool {
+        let is_mul_prefix = || {
+            if "mul(".starts_with(input) {
+                return true;
+            }
+            let Some(args) = input.strip_prefix("mul(") else { return false };
+            match args.split_once(',') {
+                None => operands.is_operand_prefix(args),
+                Some((n1, n2)) => {
+                    n1.len() >= operands.min_digits && operands.is_operand_prefix(n1) && operands.is_operand_prefix(n2)
+                }
+            }
+        };
+        match self {
+            InstructionSet::Mul => is_mul_prefix(),
+            InstructionSet::MulWithConditionals => {
+                is_mul_prefix() || "do()".starts_with(input) || "don't()".starts_with(input)
+            }
+        }
+    }
+
+    /// Scan the memory for instructions, with the puzzle's operand rules (see
+    /// `Scanner::with_operand_rules` to change them)
+    pub fn scan(self, input: &str) -> Scanner<'_> {
+        Scanner { input, pos: 0, instruction_set: self, operands: OperandRules::default() }
+    }
+
+    /// All instructions of the set in the memory (possibly none), in order
+    pub fn parse(self, input: &str) -> AnyResult<Vec<Node>> {
+        Ok(self.scan(input).collect())
+    }
+}
+
+/// Iterator over the instructions found in memory, in a single pass.
+///
+/// Only the bytes that can start an instruction are tried, everything else is skipped without
+/// parsing. When a candidate is not a valid instruction, scanning resumes right after its first
+/// byte, so it finds exactly what trying the parser at every byte would find.
+pub struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+    instruction_set: InstructionSet,
+    operands: OperandRules,
+}
+
+impl Scanner<'_> {
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_operand_rules(self, operands: OperandRules) -> Self {
+        Self { operands, ..self }
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Node> {
+        loop {
+            let candidate_offset = self.pos + self.input.as_bytes()[self.pos..]
+                .iter()
+                .position(|&byte| self.instruction_set.is_start_byte(byte))?;
+            // NOTE: Start bytes are ASCII, so always on a char boundary
+            let candidate = &self.input[candidate_offset..];
+            match self.instruction_set.parse_instruction(candidate, self.operands) {
+                Ok((rest, ast)) => {
+                    let span = Span { offset: candidate_offset, len: candidate.len() - rest.len() };
+                    self.pos = span.range().end;
+                    return Some(Node { ast, span });
+                }
+                Err(_) => self.pos = candidate_offset + 1,
+            }
+        }
+    }
+}
+
+/// Run all instructions of the set found in the memory, returns the total of the muls
+pub fn evaluate(input: &str, instruction_set: InstructionSet, operands: OperandRules) -> AnyResult<usize> {
+    evaluate_reader(input.as_bytes(), instruction_set, operands)
+}
+
+pub fn solve_part2(input: &str) -> AnyResult<Option<usize>> {
+    let total = evaluate(input, InstructionSet::MulWithConditionals, OperandRules::default())?;
+    Ok(Some(total))
+}
+
+const STYLE_ENABLED_MUL: &str = "\x1b[1;32m"; // bold green
+const STYLE_DISABLED_MUL: &str = "\x1b[2;9;31m"; // dim, struck through, red
+const STYLE_CONDITIONAL: &str = "\x1b[1;34m"; // bold blue
+const STYLE_RESET: &str = "\x1b[0m";
+
+/// The input, with the recognized instructions highlighted (with ANSI colors): enabled muls in
+/// green, disabled muls struck through in red, `do()` & `don't()` in blue.
+///
+/// The trace is the one of running these instructions. Without colors (e.g. when not printed to a
+/// terminal), it's the input as is.
+pub fn annotate_input(input: &str, nodes: &[Node], trace: &[TraceStep], colored: bool) -> String {
+    let mut annotated = String::with_capacity(input.len());
+    let mut pos = 0;
+    for (node, step) in nodes.iter().zip(trace) {
+        let style = match node.ast {
+            // NOTE: A mul doesn't change the state, so it's the same before & after it
+            Ast::MulNode(..) if step.state.muls_enabled => STYLE_ENABLED_MUL,
+            Ast::MulNode(..) => STYLE_DISABLED_MUL,
+            Ast::Do | Ast::Dont => STYLE_CONDITIONAL,
+        };
+        let (style, reset) = if colored { (style, STYLE_RESET) } else { ("", "") };
+        annotated += &input[pos..node.span.offset];
+        annotated += style;
+        annotated += &input[node.span.range()];
+        annotated += reset;
+        pos = node.span.range().end;
+    }
+    annotated += &input[pos..];
+    annotated
+}
+
+/// Annotated input (see `annotate_input`), then the execution trace of part2's instructions with
+/// the machine state after each one.
+pub fn explain_input(input: &str, colored: bool) -> AnyResult<String> {
+    let nodes = InstructionSet::MulWithConditionals.parse(input)?;
+    let mut vm = Vm::with_trace();
+    vm.run(nodes.iter().map(|node| &node.ast))?;
+
+    let mut explanation = annotate_input(input, &nodes, vm.trace(), colored);
+    if !explanation.ends_with('\n') {
+        explanation.push('\n');
+    }
+    explanation.push('\n');
+
+    let mut table = Table::new(&["Step", "Offset", "Instruction", "Muls", "Total"]);
+    for (step_idx, (node, step)) in nodes.iter().zip(vm.trace()).enumerate() {
+        table.add_row(vec![
+            (step_idx + 1).to_string(),
+            node.span.offset.to_string(),
+            input[node.span.range()].to_string(),
+            (if step.state.muls_enabled { "enabled" } else { "disabled" }).to_string(),
+            step.state.total.to_string(),
+        ]);
+    }
+    explanation += &table.to_string();
+    Ok(explanation)
+}
+
+/// The previous implementation, trying the full instruction parser at every byte (with
+/// backtracking). It's kept as a reference to check the scanner against.
+#[cfg(test)]
+mod backtracking {
+    use nom::{bytes::complete::take, combinator::not, multi::{many0_count, many1}, sequence::preceded, IResult};
+
+    use super::{Ast, Node, OperandRules, Span};
+
+    fn parse_mul(input: &str) -> IResult<&str, Ast> {
+        super::parse_mul(OperandRules::default())(input)
+    }
+
+    fn parse_ast(input: &str) -> IResult<&str, Ast> {
+        super::parse_ast(OperandRules::default())(input)
+    }
+
+    pub fn parse_char_not_mul(input: &str) -> IResult<&str, &str> {
+        // NOTE: `not(parse_mul)` succeeds when not on a mul node, but does NOT comsume any input,
+        //   so we need to manually take it ourselves.
+        // NOTE: `preceded(A, B)` discards A's output and keeps B's.
+        preceded(not(parse_mul), take(1_usize))(input)
+    }
+
+    // Wrap an instruction parser to also get where the instruction is in the full input
+    pub fn spanned<'a>(
+        full_input: &'a str,
+        mut parser: impl FnMut(&'a str) -> IResult<&'a str, Ast>,
+    ) -> impl FnMut(&'a str) -> IResult<&'a str, Node> {
+        move |input| {
+            let (rest, ast) = parser(input)?;
+            // NOTE: `input` & `rest` are suffixes of the full input
+            let span = Span {
+                offset: full_input.len() - input.len(),
+                len: input.len() - rest.len(),
+            };
+            Ok((rest, Node { ast, span }))
+        }
+    }
+
+    pub fn parse_input_with_muls(input: &str) -> IResult<&str, Vec<Node>> {
+        // NOTE: using many0_count instead of many0 to avoid collecting chars (will be discarded anyway)
+        let parse_any_gibberish = many0_count(parse_char_not_mul);
+        let (rest, mul_nodes) = many1(preceded(parse_any_gibberish, spanned(input, parse_mul)))(input)?;
+        Ok((rest, mul_nodes))
+    }
+
+    pub fn parse_char_not_ast(input: &str) -> IResult<&str, &str> {
+        // REF: see `parse_char_not_mul`
+        preceded(not(parse_ast), take(1_usize))(input)
+    }
+
+    pub fn parse_input_with_ast(input: &str) -> IResult<&str, Vec<Node>> {
+        // NOTE: using many0_count instead of many0 to avoid collecting chars (will be discarded anyway)
+        let parse_any_gibberish = many0_count(parse_char_not_ast);
+        let (rest, ast_nodes) = many1(preceded(parse_any_gibberish, spanned(input, parse_ast)))(input)?;
+        Ok((rest, ast_nodes))
+    }
+}
+
+// ----------------------------------------------------
+
+/// Generate about `size` bytes of corrupted memory, with valid & corrupted instructions.
+pub fn generate_input(size: usize, rng: &mut Rng) -> String {
+    const GIBBERISH_CHARS: &[char] = &[
+        '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '[', ']', '{', '}', '<', '>', '?', ':', ';',
+        '\'', '~', '+', '-', ',', '_', ' ', '/',
+    ];
+    const GIBBERISH_WORDS: &[&str] = &["select()", "why()", "how()", "when()", "where(", "from()", "who(", "what()"];
+    const CORRUPTED_MULS: &[&str] = &[
+        "mul[3,7]", "mul(32,64]", "mul ( 2 , 4 )", "mul(4*", "mul(6,9!", "?(12,34)", "mul(,1)", "mu(1,2)",
+        "mul(1,)", "don't", "do(", "mul", "do_not_", "undo(",
+    ];
+
+    let mut input = String::new();
+    while input.len() < size {
+        match rng.gen_range(0..100) {
+            0..20 => {
+                let n1 = rng.gen_range(1..1000);
+                let n2 = rng.gen_range(1..1000);
+                input += &format!("mul({n1},{n2})");
+            }
+            20..25 => input += "do()",
+            25..30 => input += "don't()",
+            30..40 => input += *rng.choose(CORRUPTED_MULS),
+            40..50 => input += *rng.choose(GIBBERISH_WORDS),
+            50..51 => input.push('\n'),
+            _ => input.push(*rng.choose(GIBBERISH_CHARS)),
+        }
+    }
+    input
+}
+
+// ----------------------------------------------------
+
+#[cfg(test)]
+mod tests_part1 {
+    use nom::multi::many0;
+
+    use super::*;
+    use super::backtracking::*;
+    use super::Ast::MulNode;
+
+    const EXAMPLE_INPUT: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+
+    #[test]
+    fn test_parse_mul_node() {
+        let input = "mul(123,42)bla";
+        let res = parse_mul(OperandRules::default())(input);
+        dbg!(&res);
+        let (rest, node) = res.unwrap();
+        assert_eq!(rest, "bla");
+        assert_eq!(node, MulNode(123, 42));
+    }
+
+    #[test]
+    fn test_parse_gibberish_until_mul() {
+        let input = "xyzmul(1,2)bla";
+        let res = many0(parse_char_not_mul)(input);
+        dbg!(&res);
+        let (rest, gibberish) = res.unwrap();
+        assert_eq!(rest, "mul(1,2)bla");
+        assert_eq!(gibberish, vec!["x", "y", "z"]);
+    }
+
+    // NOTE: missing gibberish must be ok to work as separator between good mul nodes
+    #[test]
+    fn test_parse_missing_gibberish_as_ok() {
+        let input = "mul(41,42)bla"; // zero gibberish before mul node
+        let res = many0(parse_char_not_mul)(input);
+        dbg!(&res);
+        let (rest, gibberish) = res.unwrap();
+        assert_eq!(rest, "mul(41,42)bla");
+        assert_eq!(gibberish.len(), 0);
+    }
+
+    #[test]
+    fn test_parse_chained_muls() {
+        let input = "mul(1,2)mul(3,4)bla";
+        // mul nodes:^------^^------^
+        let res = parse_input_with_muls(input);
+        dbg!(&res);
+        let (rest, parsed) = res.unwrap();
+        assert_eq!(rest, "bla");
+        assert_eq!(parsed, vec![
+            Node { ast: MulNode(1, 2), span: Span { offset: 0, len: 8 } },
+            Node { ast: MulNode(3, 4), span: Span { offset: 8, len: 8 } },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_separated_muls() {
+        let input = "xmul(2,4)%&mul[3,7]!do_not_mul(512,74)+mul(32,64]t";
+        // mul nodes: ^------^                  ^---------^
+        let res = parse_input_with_muls(input);
+        dbg!(&res);
+        let (rest, parsed) = res.unwrap();
+        assert_eq!(rest, "+mul(32,64]t");
+        let asts: Vec<Ast> = parsed.iter().map(|node| node.ast).collect();
+        assert_eq!(asts, vec![MulNode(2, 4), MulNode(512, 74)]);
+        let instructions: Vec<&str> = parsed.iter().map(|node| &input[node.span.range()]).collect();
+        assert_eq!(instructions, vec!["mul(2,4)", "mul(512,74)"]);
+    }
+
+    #[test]
+    fn test_operand_digit_limits() {
+        let parse = |input, operands| parse_mul(operands)(input).map(|(_, ast)| ast).ok();
+        let puzzle = OperandRules::default();
+        assert_eq!(parse("mul(123,4)", puzzle), Some(MulNode(123, 4)));
+        assert_eq!(parse("mul(1234,5)", puzzle), None);
+        assert_eq!(parse("mul(5,1234)", puzzle), None);
+        // Leading zeros count as digits
+        assert_eq!(parse("mul(0012,3)", puzzle), None);
+        let unlimited = OperandRules::unlimited_digits(Overflow::Reject);
+        assert_eq!(parse("mul(1234,5)", unlimited), Some(MulNode(1234, 5)));
+        assert_eq!(parse("mul(0012,3)", unlimited), Some(MulNode(12, 3)));
+        let at_least_2 = OperandRules { min_digits: 2, ..puzzle };
+        assert_eq!(parse("mul(1,23)", at_least_2), None);
+        assert_eq!(parse("mul(01,23)", at_least_2), Some(MulNode(1, 23)));
+    }
+
+    #[test]
+    fn test_operand_overflow() {
+        let parse = |input, overflow| {
+            parse_mul(OperandRules::unlimited_digits(overflow))(input).map(|(_, ast)| ast).ok()
+        };
+        assert_eq!(parse("mul(99999999999,1)", Overflow::Reject), None);
+        assert_eq!(parse("mul(99999999999,1)", Overflow::Saturate), Some(MulNode(u32::MAX as u64, 1)));
+        assert_eq!(parse("mul(99999999999,1)", Overflow::Wide), Some(MulNode(99999999999, 1)));
+        // Even too big for a `u64`
+        let huge = "mul(99999999999999999999999,1)";
+        assert_eq!(parse(huge, Overflow::Reject), None);
+        assert_eq!(parse(huge, Overflow::Saturate), Some(MulNode(u32::MAX as u64, 1)));
+        assert_eq!(parse(huge, Overflow::Wide), None);
+        // The limit is inclusive
+        assert_eq!(parse("mul(4294967295,1)", Overflow::Reject), Some(MulNode(u32::MAX as u64, 1)));
+        assert_eq!(parse("mul(4294967296,1)", Overflow::Reject), None);
+    }
+
+    #[test]
+    fn test_evaluate_with_operand_rules() {
+        let input = "mul(1234,5)mul(99999999999,1)mul(2,3)";
+        assert_eq!(solve_part1(input).unwrap(), Some(6));
+        let evaluate_with = |overflow| evaluate(input, InstructionSet::Mul, OperandRules::unlimited_digits(overflow)).unwrap();
+        assert_eq!(evaluate_with(Overflow::Reject), 6170 + 6);
+        assert_eq!(evaluate_with(Overflow::Saturate), 6170 + u32::MAX as usize + 6);
+        assert_eq!(evaluate_with(Overflow::Wide), 6170 + 99999999999 + 6);
+        // Wide operands can make the product itself overflow
+        let err = evaluate("mul(18446744073709551615,2)", InstructionSet::Mul, OperandRules::unlimited_digits(Overflow::Wide))
+            .unwrap_err();
+        assert_eq!(format!("{err:#}"), "Failed to execute MulNode(18446744073709551615, 2): Product overflows");
+        // Rules are used by the scanner too
+        let nodes: Vec<Node> = InstructionSet::Mul.scan(input)
+            .with_operand_rules(OperandRules::unlimited_digits(Overflow::Saturate))
+            .collect();
+        assert_eq!(nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_part1() {
+        let res = solve_part1(EXAMPLE_INPUT).unwrap();
+        assert_eq!(res, Some(161));
+    }
+}
+
+#[cfg(test)]
+mod tests_part2 {
+    use std::time::Instant;
+
+    use super::*;
+    use super::backtracking::*;
+
+    const EXAMPLE_INPUT: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    // ast nodes:                 ^------^           ^-----^   (mul nodes disabled here)    ^--^ ^------^
+    // effect:                                      (disables)                           (enables)
+
+    #[test]
+    fn test_parse_do_node() {
+        let input = "do()bla";
+        let res = parse_do(input);
+        dbg!(&res);
+        let (rest, node) = res.unwrap();
+        assert_eq!(rest, "bla");
+        assert_eq!(node, Ast::Do);
+    }
+
+    #[test]
+    fn test_parse_dont_node() {
+        let input = "don't()bla";
+        let res = parse_dont(input);
+        dbg!(&res);
+        let (rest, node) = res.unwrap();
+        assert_eq!(rest, "bla");
+        assert_eq!(node, Ast::Dont);
+    }
+
+    #[test]
+    fn test_parse_input_with_ast() {
+        let input = "xmul(2,4)&mul[3do(),7]!^dont()!mul(41,42)_undon't()bla)";
+        // ast nodes: ^------^      ^-----^         ^--------^     ^--^
+        let res = parse_input_with_ast(input);
+        dbg!(&res);
+        let (rest, parsed) = res.unwrap();
+        assert_eq!(rest, "bla)");
+        let asts: Vec<Ast> = parsed.iter().map(|node| node.ast).collect();
+        assert_eq!(asts, vec![
+            Ast::MulNode(2, 4),
+            Ast::Do,
+            Ast::MulNode(41, 42),
+            Ast::Dont,
+        ]);
+    }
+
+    #[test]
+    fn test_scanner_matches_backtracking_parser() {
+        let input = generate_input(100_000, &mut Rng::new(42));
+        let (_, expected_muls) = parse_input_with_muls(&input).unwrap();
+        assert_eq!(InstructionSet::Mul.parse(&input).unwrap(), expected_muls);
+        let (_, expected_nodes) = parse_input_with_ast(&input).unwrap();
+        assert_eq!(InstructionSet::MulWithConditionals.parse(&input).unwrap(), expected_nodes);
+    }
+
+    #[test]
+    fn test_scanner_edge_cases() {
+        let scan = |input| InstructionSet::MulWithConditionals.parse(input).unwrap();
+        // Without any instruction, the total is just 0
+        assert_eq!(scan(""), vec![]);
+        assert_eq!(scan("xyz don't"), vec![]);
+        assert_eq!(solve_part1("xyz").unwrap(), Some(0));
+        // A failed candidate doesn't hide an instruction starting inside it
+        assert_eq!(scan("mumul(1,2)dodo()"), vec![
+            Node { ast: Ast::MulNode(1, 2), span: Span { offset: 2, len: 8 } },
+            Node { ast: Ast::Do, span: Span { offset: 12, len: 4 } },
+        ]);
+        // Non-ASCII gibberish
+        assert_eq!(scan("é💥mul(1,2)"), vec![Node { ast: Ast::MulNode(1, 2), span: Span { offset: 6, len: 8 } }]);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with: cargo test --release -- --ignored bench_"]
+    fn bench_scanner_vs_backtracking_parser() {
+        let input = generate_input(5_000_000, &mut Rng::new(42));
+
+        let start = Instant::now();
+        let nodes = InstructionSet::MulWithConditionals.parse(&input).unwrap();
+        let scanner_duration = start.elapsed();
+
+        let start = Instant::now();
+        let (_, expected_nodes) = parse_input_with_ast(&input).unwrap();
+        let backtracking_duration = start.elapsed();
+
+        println!(
+            "parsing {size} MB: scanner {scanner_duration:?}, backtracking {backtracking_duration:?}",
+            size = input.len() / 1_000_000,
+        );
+        assert_eq!(nodes, expected_nodes);
+        assert!(scanner_duration < backtracking_duration);
+    }
+
+    #[test]
+    fn test_execution_trace() {
+        let nodes = InstructionSet::MulWithConditionals.parse(EXAMPLE_INPUT).unwrap();
+        let mut vm = Vm::with_trace();
+        vm.run(nodes.iter().map(|node| &node.ast)).unwrap();
+        let trace: Vec<_> = vm.trace().iter()
+            .map(|step| (step.instruction.as_str(), step.state.muls_enabled, step.state.total))
+            .collect();
+        assert_eq!(trace, vec![
+            ("MulNode(2, 4)", true, 8),
+            ("Dont", false, 8),
+            ("MulNode(5, 5)", false, 8),
+            ("MulNode(11, 8)", false, 8),
+            ("Do", true, 8),
+            ("MulNode(8, 5)", true, 48),
+        ]);
+    }
+
+    #[test]
+    fn test_spans() {
+        let nodes = parse_input(EXAMPLE_INPUT).unwrap();
+        let spans: Vec<(usize, &str)> = nodes.iter()
+            .map(|node| (node.span.offset, &EXAMPLE_INPUT[node.span.range()]))
+            .collect();
+        assert_eq!(spans, vec![
+            (1, "mul(2,4)"),
+            (20, "don't()"),
+            (28, "mul(5,5)"),
+            (48, "mul(11,8)"),
+            (59, "do()"),
+            (64, "mul(8,5)"),
+        ]);
+        // `mul[3,7]` & `mul(32,64]` are rejected, so nothing starts there
+        assert!(nodes.iter().all(|node| node.span.offset != 10 && node.span.offset != 37));
+    }
+
+    fn trace_of(nodes: &[Node]) -> Vec<TraceStep> {
+        let mut vm = Vm::with_trace();
+        vm.run(nodes.iter().map(|node| &node.ast)).unwrap();
+        vm.trace().to_vec()
+    }
+
+    #[test]
+    fn test_annotate_input() {
+        let input = "xmul(2,4)don't()mul(5,5)?do()";
+        let nodes = parse_input(input).unwrap();
+        let trace = trace_of(&nodes);
+        let annotated = annotate_input(input, &nodes, &trace, true);
+        let expected = format!(
+            "x{on}mul(2,4){reset}{cond}don't(){reset}{off}mul(5,5){reset}?{cond}do(){reset}",
+            on = STYLE_ENABLED_MUL,
+            off = STYLE_DISABLED_MUL,
+            cond = STYLE_CONDITIONAL,
+            reset = STYLE_RESET,
+        );
+        assert_eq!(annotated, expected);
+        assert_eq!(annotate_input(input, &nodes, &trace, false), input);
+    }
+
+    #[test]
+    fn test_explain_input() {
+        let explanation = explain_input(EXAMPLE_INPUT, true).unwrap();
+        let nodes = parse_input(EXAMPLE_INPUT).unwrap();
+        assert!(explanation.starts_with(&annotate_input(EXAMPLE_INPUT, &nodes, &trace_of(&nodes), true)));
+        assert!(!explain_input(EXAMPLE_INPUT, false).unwrap().contains('\x1b'));
+        assert!(explanation.contains("\n3    | 28     | mul(5,5)    | disabled | 8\n"));
+    }
+
+    #[test]
+    fn test_part2() {
+        let res = solve_part2(EXAMPLE_INPUT).unwrap();
+        assert_eq!(res, Some(48));
+    }
+
+    #[test]
+    fn test_generated_input() {
+        let input = generate_input(1000, &mut Rng::new(42));
+        assert_eq!(input, generate_input(1000, &mut Rng::new(42)));
+        let (_, nodes) = parse_input_with_ast(&input).unwrap();
+        assert!(nodes.iter().any(|node| node.ast == Ast::Do));
+        assert!(nodes.iter().any(|node| node.ast == Ast::Dont));
+        assert!(solve_part2(&input).unwrap() < solve_part1(&input).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::strategies;
+
+    // Parse `mul(X,Y)` at the start of the string, returns the operands & length of the instruction
+    fn mul_reference(memory: &str) -> Option<(usize, usize, usize)> {
+        let parse_number = |text: &str| -> Option<(usize, usize)> {
+            let num_digits = text.chars().take_while(char::is_ascii_digit).count();
+            if !(1..=3).contains(&num_digits) {
+                return None;
+            }
+            let number: u32 = text[..num_digits].parse().ok()?;
+            Some((number as usize, num_digits))
+        };
+        let args = memory.strip_prefix("mul(")?;
+        let (n1, n1_len) = parse_number(args)?;
+        let args = args[n1_len..].strip_prefix(',')?;
+        let (n2, n2_len) = parse_number(args)?;
+        args[n2_len..].strip_prefix(')')?;
+        Some((n1, n2, "mul(,)".len() + n1_len + n2_len))
+    }
+
+    // Walk the memory char by char, looking for instructions
+    fn evaluate_reference(memory: &str, with_conditionals: bool) -> usize {
+        let mut enabled = true;
+        let mut total = 0;
+        let mut rest = memory;
+        while !rest.is_empty() {
+            if with_conditionals && rest.starts_with("do()") {
+                enabled = true;
+                rest = &rest["do()".len()..];
+            } else if with_conditionals && rest.starts_with("don't()") {
+                enabled = false;
+                rest = &rest["don't()".len()..];
+            } else if let Some((n1, n2, len)) = mul_reference(rest) {
+                if enabled {
+                    total += n1 * n2;
+                }
+                rest = &rest[len..];
+            } else {
+                let mut chars = rest.chars();
+                chars.next();
+                rest = chars.as_str();
+            }
+        }
+        total
+    }
+
+    proptest! {
+        #[test]
+        fn part1_matches_reference(memory in strategies::corrupted_memory()) {
+            prop_assert_eq!(solve_part1(&memory).unwrap(), Some(evaluate_reference(&memory, false)));
+        }
+
+        #[test]
+        fn part2_matches_reference(memory in strategies::corrupted_memory()) {
+            prop_assert_eq!(solve_part2(&memory).unwrap(), Some(evaluate_reference(&memory, true)));
+        }
+
+        #[test]
+        fn scanner_matches_backtracking_parser(memory in strategies::corrupted_memory()) {
+            // NOTE: The backtracking parser fails when there is no instruction at all
+            let expected_nodes = backtracking::parse_input_with_ast(&memory).map_or(vec![], |(_, nodes)| nodes);
+            prop_assert_eq!(InstructionSet::MulWithConditionals.parse(&memory).unwrap(), expected_nodes);
+            let expected_muls = backtracking::parse_input_with_muls(&memory).map_or(vec![], |(_, nodes)| nodes);
+            prop_assert_eq!(InstructionSet::Mul.parse(&memory).unwrap(), expected_muls);
+        }
+
+        #[test]
+        fn part2_is_at_most_part1(memory in strategies::corrupted_memory()) {
+            prop_assert!(solve_part2(&memory).unwrap() <= solve_part1(&memory).unwrap());
+        }
+    }
+}
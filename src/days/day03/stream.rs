@@ -0,0 +1,304 @@
+//! Evaluate corrupted memory from any reader, one chunk at a time.
+//!
+//! Only a chunk of the memory is kept around, plus the start of an instruction that may continue
+//! in the next chunk, so it works on memory of any size (like gigabytes of generated input).
+
+use std::io::BufRead;
+use std::str;
+
+use anyhow::{bail, Context, Result as AnyResult};
+
+use super::vm::Vm;
+use super::{InstructionSet, Node, OperandRules, Span};
+
+/// Max number of bytes taken from the reader at once
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Like `Scanner`, but reading the memory from a `BufRead`.
+///
+/// When an instruction can't be parsed at the end of the current chunk, it's only rejected if no
+/// more bytes could make it valid. Otherwise the next chunk is read and it's tried again.
+pub struct StreamScanner<R> {
+    reader: R,
+    instruction_set: InstructionSet,
+    operands: OperandRules,
+    /// The part of the memory not yet scanned (and the start of an instruction, if any)
+    buffer: String,
+    /// Bytes read but not yet in `buffer`, at most the start of a UTF-8 char split between chunks
+    undecoded: Vec<u8>,
+    /// Where scanning resumes in `buffer`
+    pos: usize,
+    /// Offset of the start of `buffer` in the full memory
+    buffer_offset: usize,
+    eof: bool,
+}
+
+impl<R: BufRead> StreamScanner<R> {
+    pub fn new(reader: R, instruction_set: InstructionSet, operands: OperandRules) -> Self {
+        Self {
+            reader,
+            instruction_set,
+            operands,
+            buffer: String::new(),
+            undecoded: vec![],
+            pos: 0,
+            buffer_offset: 0,
+            eof: false,
+        }
+    }
+
+    /// Drop the scanned part of the buffer, and append the next chunk of the memory
+    fn read_chunk(&mut self) -> AnyResult<()> {
+        self.buffer.drain(..self.pos);
+        self.buffer_offset += self.pos;
+        self.pos = 0;
+
+        let undecoded_offset = self.buffer_offset + self.buffer.len();
+        let bytes = self.reader.fill_buf()
+            .with_context(|| format!("Failed to read memory at byte {}", undecoded_offset + self.undecoded.len()))?;
+        if bytes.is_empty() {
+            self.eof = true;
+            if !self.undecoded.is_empty() {
+                bail!("Invalid UTF-8 at byte {undecoded_offset}: memory ends in the middle of a char");
+            }
+            return Ok(());
+        }
+        let chunk_len = bytes.len().min(CHUNK_SIZE);
+        self.undecoded.extend_from_slice(&bytes[..chunk_len]);
+        self.reader.consume(chunk_len);
+
+        // NOTE: The end of the chunk may be the start of a char continuing in the next one
+        let valid_len = match str::from_utf8(&self.undecoded) {
+            Ok(_) => self.undecoded.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => bail!("Invalid UTF-8 at byte {}", undecoded_offset + err.valid_up_to()),
+        };
+        // NOTE: Already checked just above
+        self.buffer.push_str(str::from_utf8(&self.undecoded[..valid_len]).unwrap());
+        self.undecoded.drain(..valid_len);
+        Ok(())
+    }
+
+    fn next_node(&mut self) -> AnyResult<Option<Node>> {
+        loop {
+            let Some(candidate_offset) = self.buffer.as_bytes()[self.pos..]
+                .iter()
+                .position(|&byte| self.instruction_set.is_start_byte(byte))
+                .map(|idx| self.pos + idx)
+            else {
+                self.pos = self.buffer.len();
+                if self.eof {
+                    return Ok(None);
+                }
+                self.read_chunk()?;
+                continue;
+            };
+            let candidate = &self.buffer[candidate_offset..];
+            match self.instruction_set.parse_instruction(candidate, self.operands) {
+                Ok((rest, ast)) => {
+                    let len = candidate.len() - rest.len();
+                    self.pos = candidate_offset + len;
+                    let span = Span { offset: self.buffer_offset + candidate_offset, len };
+                    return Ok(Some(Node { ast, span }));
+                }
+                Err(_) if !self.eof && self.instruction_set.is_instruction_prefix(candidate, self.operands) => {
+                    // NOTE: Keep the candidate, we'll know if it's valid with the next chunk
+                    self.pos = candidate_offset;
+                    self.read_chunk()?;
+                }
+                Err(_) => self.pos = candidate_offset + 1,
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StreamScanner<R> {
+    type Item = AnyResult<Node>;
+
+    fn next(&mut self) -> Option<AnyResult<Node>> {
+        self.next_node().transpose()
+    }
+}
+
+/// Run all instructions of the set found in the memory read from `reader`, returns the total of
+/// the muls
+pub fn evaluate_reader(reader: impl BufRead, instruction_set: InstructionSet, operands: OperandRules) -> AnyResult<usize> {
+    let mut vm = Vm::new();
+    for node in StreamScanner::new(reader, instruction_set, operands) {
+        vm.execute(&node?.ast)?;
+    }
+    Ok(vm.machine().total)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufReader, Read};
+
+    use super::*;
+    use crate::days::day03::{generate_input, Ast, Overflow};
+    use crate::utils::rng::Rng;
+
+    // Reader giving at most `max_read` bytes at a time, to split the memory in lots of chunks
+    struct SmallReads<'a> {
+        data: &'a [u8],
+        max_read: usize,
+    }
+    impl Read for SmallReads<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.max_read).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn scan_in_chunks(memory: &str, chunk_size: usize, instruction_set: InstructionSet) -> AnyResult<Vec<Node>> {
+        let reader = BufReader::with_capacity(chunk_size, memory.as_bytes());
+        StreamScanner::new(reader, instruction_set, OperandRules::default()).collect()
+    }
+
+    #[test]
+    fn test_instructions_split_across_chunks() {
+        let memory = "xmul(2,4)don't()mul(5,5)?do()mul(123,456)é💥mul(1,2)mul(1,2";
+        for instruction_set in [InstructionSet::Mul, InstructionSet::MulWithConditionals] {
+            let expected = instruction_set.parse(memory).unwrap();
+            for chunk_size in 1..=memory.len() {
+                assert_eq!(scan_in_chunks(memory, chunk_size, instruction_set).unwrap(), expected, "chunk size: {chunk_size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefix_of_instruction_at_end_of_chunk() {
+        // `mul(12` ends the first chunk: it's kept until we know the next byte isn't a digit
+        let memory = "abcmul(12,3)";
+        let reader = BufReader::with_capacity(6, memory.as_bytes());
+        let nodes: Vec<Node> = StreamScanner::new(reader, InstructionSet::Mul, OperandRules::default()).collect::<AnyResult<_>>().unwrap();
+        assert_eq!(nodes, vec![Node { ast: Ast::MulNode(12, 3), span: Span { offset: 3, len: 9 } }]);
+    }
+
+    #[test]
+    fn test_operand_rules_across_chunks() {
+        let memory = "mul(1234,5)?mul(99999999999,1)mul(0002,3)";
+        for overflow in [Overflow::Reject, Overflow::Saturate, Overflow::Wide] {
+            let operands = OperandRules::unlimited_digits(overflow);
+            let expected: Vec<Node> = InstructionSet::Mul.scan(memory).with_operand_rules(operands).collect();
+            for chunk_size in 1..=memory.len() {
+                let reader = BufReader::with_capacity(chunk_size, memory.as_bytes());
+                let nodes = StreamScanner::new(reader, InstructionSet::Mul, operands).collect::<AnyResult<Vec<_>>>().unwrap();
+                assert_eq!(nodes, expected, "overflow: {overflow:?}, chunk size: {chunk_size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reader() {
+        let memory = generate_input(100_000, &mut Rng::new(42));
+        for instruction_set in [InstructionSet::Mul, InstructionSet::MulWithConditionals] {
+            // NOTE: `evaluate` streams the memory too, so check against the in-memory scanner
+            let mut vm = Vm::new();
+            vm.run(instruction_set.parse(&memory).unwrap().iter().map(|node| &node.ast)).unwrap();
+            let expected = vm.machine().total;
+            let reader = BufReader::new(SmallReads { data: memory.as_bytes(), max_read: 37 });
+            assert_eq!(evaluate_reader(reader, instruction_set, OperandRules::default()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let err = evaluate_reader(&b"mul(1,2)\xffmul(3,4)"[..], InstructionSet::Mul, OperandRules::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-8 at byte 8");
+        // A char split between chunks is fine, but not one cut by the end of the memory
+        let reader = BufReader::with_capacity(1, "é".as_bytes());
+        assert_eq!(evaluate_reader(reader, InstructionSet::Mul, OperandRules::default()).unwrap(), 0);
+        let err = evaluate_reader(&"mul(1,2)é".as_bytes()[..9], InstructionSet::Mul, OperandRules::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-8 at byte 8: memory ends in the middle of a char");
+    }
+
+    // Generated memory of the given size, generated one piece at a time as it's read
+    struct GeneratedMemory {
+        rng: Rng,
+        remaining: usize,
+        piece: Vec<u8>,
+        piece_pos: usize,
+    }
+    impl Read for GeneratedMemory {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.piece_pos == self.piece.len() {
+                if self.remaining == 0 {
+                    return Ok(0);
+                }
+                let size = self.remaining.min(CHUNK_SIZE);
+                self.piece = generate_input(size, &mut self.rng).into_bytes();
+                self.piece_pos = 0;
+                self.remaining = self.remaining.saturating_sub(self.piece.len());
+            }
+            let len = buf.len().min(self.piece.len() - self.piece_pos);
+            buf[..len].copy_from_slice(&self.piece[self.piece_pos..self.piece_pos + len]);
+            self.piece_pos += len;
+            Ok(len)
+        }
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_memory_use_doesnt_depend_on_input_size() {
+        let reader = BufReader::new(GeneratedMemory { rng: Rng::new(42), remaining: 20_000_000, piece: vec![], piece_pos: 0 });
+        let (total, stats) = crate::alloc_stats::measure(|| evaluate_reader(reader, InstructionSet::MulWithConditionals, OperandRules::default()));
+        assert!(total.unwrap() > 0);
+        let stats = stats.unwrap();
+        assert!(stats.peak_live_bytes < 1024 * 1024, "peak memory: {stats}");
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with: cargo test --release -- --ignored bench_"]
+    fn bench_evaluate_reader_on_1gb() {
+        let reader = BufReader::new(GeneratedMemory { rng: Rng::new(42), remaining: 1_000_000_000, piece: vec![], piece_pos: 0 });
+        let start = std::time::Instant::now();
+        let total = evaluate_reader(reader, InstructionSet::MulWithConditionals, OperandRules::default()).unwrap();
+        println!("evaluating 1 GB of streamed memory: {:?} (total: {total})", start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use std::io::BufReader;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::day03::Overflow;
+    use crate::utils::strategies;
+
+    fn operand_rules() -> impl Strategy<Value = OperandRules> {
+        let overflow = prop_oneof![Just(Overflow::Reject), Just(Overflow::Saturate), Just(Overflow::Wide)];
+        (1..4usize, 0..25usize, overflow).prop_map(|(min_digits, extra_digits, overflow)| {
+            OperandRules { min_digits, max_digits: min_digits + extra_digits, overflow }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn stream_matches_in_memory_scanner(memory in strategies::corrupted_memory(), chunk_size in 1..20usize) {
+            for instruction_set in [InstructionSet::Mul, InstructionSet::MulWithConditionals] {
+                let reader = BufReader::with_capacity(chunk_size, memory.as_bytes());
+                let nodes = StreamScanner::new(reader, instruction_set, OperandRules::default()).collect::<AnyResult<Vec<_>>>().unwrap();
+                prop_assert_eq!(nodes, instruction_set.parse(&memory).unwrap());
+            }
+        }
+
+        #[test]
+        fn stream_matches_in_memory_scanner_with_operand_rules(
+            memory in strategies::corrupted_memory(),
+            chunk_size in 1..20usize,
+            operands in operand_rules(),
+        ) {
+            let reader = BufReader::with_capacity(chunk_size, memory.as_bytes());
+            let nodes = StreamScanner::new(reader, InstructionSet::MulWithConditionals, operands)
+                .collect::<AnyResult<Vec<_>>>()
+                .unwrap();
+            let expected: Vec<Node> = InstructionSet::MulWithConditionals.scan(&memory).with_operand_rules(operands).collect();
+            prop_assert_eq!(nodes, expected);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Small machine running the instructions found in corrupted memory.
+//!
+//! Instructions are anything implementing `Instruction`, the machine only knows its state, so
+//! both parts (and any new instruction) run on the same `Vm`.
+
+use std::fmt;
+
+use anyhow::{Context, Result as AnyResult};
+
+/// State of the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Machine {
+    /// Whether `mul` instructions are enabled (changed by `do()` & `don't()`)
+    pub muls_enabled: bool,
+    /// Sum of the results of all enabled `mul` instructions
+    pub total: usize,
+}
+impl Default for Machine {
+    fn default() -> Self {
+        Self { muls_enabled: true, total: 0 }
+    }
+}
+impl Machine {
+    pub fn add_to_total(&mut self, value: usize) -> AnyResult<()> {
+        self.total = self.total.checked_add(value).context("Total overflows")?;
+        Ok(())
+    }
+}
+
+/// An instruction the machine can execute
+pub trait Instruction: fmt::Debug {
+    fn execute(&self, machine: &mut Machine) -> AnyResult<()>;
+}
+
+/// An executed instruction, with the state of the machine after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub instruction: String,
+    pub state: Machine,
+}
+
+#[derive(Debug, Default)]
+pub struct Vm {
+    machine: Machine,
+    /// Only recorded when asked (see `Vm::with_trace`), it grows with each instruction
+    trace: Option<Vec<TraceStep>>,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A machine recording a trace of all executed instructions
+    pub fn with_trace() -> Self {
+        Self { trace: Some(vec![]), ..Self::default() }
+    }
+
+    pub fn execute(&mut self, instruction: &dyn Instruction) -> AnyResult<()> {
+        instruction.execute(&mut self.machine)
+            .with_context(|| format!("Failed to execute {instruction:?}"))?;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceStep {
+                instruction: format!("{instruction:?}"),
+                state: self.machine,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn run<'a, I: Instruction + 'a>(&mut self, instructions: impl IntoIterator<Item = &'a I>) -> AnyResult<()> {
+        for instruction in instructions {
+            self.execute(instruction)?;
+        }
+        Ok(())
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    /// Executed instructions, empty if the trace is not recorded
+    pub fn trace(&self) -> &[TraceStep] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Instructions that don't exist in the puzzle, to check the machine doesn't depend on them
+    #[derive(Debug)]
+    struct Add(usize);
+    impl Instruction for Add {
+        fn execute(&self, machine: &mut Machine) -> AnyResult<()> {
+            machine.add_to_total(self.0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Toggle;
+    impl Instruction for Toggle {
+        fn execute(&self, machine: &mut Machine) -> AnyResult<()> {
+            machine.muls_enabled = !machine.muls_enabled;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_custom_instructions_with_trace() {
+        let mut vm = Vm::with_trace();
+        vm.run(&[Add(1), Add(41)]).unwrap();
+        vm.execute(&Toggle).unwrap();
+        assert_eq!(vm.machine(), &Machine { muls_enabled: false, total: 42 });
+        assert_eq!(vm.trace(), &[
+            TraceStep { instruction: "Add(1)".to_string(), state: Machine { muls_enabled: true, total: 1 } },
+            TraceStep { instruction: "Add(41)".to_string(), state: Machine { muls_enabled: true, total: 42 } },
+            TraceStep { instruction: "Toggle".to_string(), state: Machine { muls_enabled: false, total: 42 } },
+        ]);
+    }
+
+    #[test]
+    fn test_no_trace_by_default() {
+        let mut vm = Vm::new();
+        vm.run(&[Add(1), Add(2)]).unwrap();
+        assert_eq!(vm.machine().total, 3);
+        assert!(vm.trace().is_empty());
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let mut vm = Vm::new();
+        let err = vm.run(&[Add(usize::MAX), Add(1)]).unwrap_err();
+        assert_eq!(format!("{err:#}"), "Failed to execute Add(1): Total overflows");
+        assert_eq!(vm.machine().total, usize::MAX);
+    }
+}
@@ -0,0 +1,312 @@
+mod word_search;
+
+use anyhow::Result as AnyResult;
+
+use crate::utils::grid::{CharGrid, Direction, Grid, Pos};
+use crate::utils::parsing::parse_char_grid;
+use crate::utils::rng::Rng;
+pub use word_search::WordFinder;
+
+pub fn parse_input(input: &str) -> AnyResult<Grid> {
+    parse_char_grid(input)
+}
+
+#[derive(Debug)]
+struct WordSearch {
+    char_positions: Vec<(Pos, char)>,
+    #[allow(dead_code)] // needed because never read, but useful for debugs
+    dir: Direction,
+}
+impl WordSearch {
+    fn from_word_and_vector(word: &str, start_pos: &Pos, dir: &Direction) -> Self {
+        let mut next_pos = *start_pos;
+        let mut char_positions = vec![];
+        for chr in word.chars() {
+            char_positions.push((next_pos, chr));
+            next_pos = dir.next_pos(&next_pos);
+        }
+        Self { char_positions, dir: *dir }
+    }
+}
+
+fn is_word_matching(grid: &Grid, word_search: &WordSearch) -> bool {
+    word_search.char_positions
+        .iter()
+        .all(|&(pos, expected_chr)| {
+            let Some(chr) = grid.get_at(&pos) else {
+                // No char at that pos, no match
+                return false;
+            };
+            chr == expected_chr
+        })
+}
+
+pub fn solve_part1(input: &str) -> AnyResult<Option<usize>> {
+    let grid = parse_input(input)?;
+    let finder = WordFinder::new(&["XMAS"])?;
+    let num_xmas_matches = finder.find_all(&grid).len();
+    Ok(Some(num_xmas_matches))
+}
+
+// ----------------------------------------------------
+
+fn x_shape_word_searches_around_pos(word: &str, pos: &Pos) -> [WordSearch; 4] {
+    let diag_word_search = |dir: &Direction| {
+        // To get diagonal char positions, we go back 1 then go forward in dir
+        WordSearch::from_word_and_vector(word, &dir.reverse_dir().next_pos(pos), dir)
+    };
+    [
+        diag_word_search(&Direction::TopLeft),
+        diag_word_search(&Direction::TopRight),
+        diag_word_search(&Direction::BottomRight),
+        diag_word_search(&Direction::BottomLeft),
+    ]
+}
+
+pub fn solve_part2(input: &str) -> AnyResult<Option<usize>> {
+    const WORD_SEARCH: &str = "MAS";
+    let grid = parse_input(input)?;
+
+    let all_a_iter = grid.iter_with_position()
+        .filter(|&(_, chr)| chr == 'A');
+
+    // For all starting positions of the X for XMAS, search for the word in all directions
+    let mut num_x_shape_mas_matches = 0;
+    for (pos, _x_chr) in all_a_iter {
+        let searches = x_shape_word_searches_around_pos(WORD_SEARCH, &pos);
+        let num_matches = searches.into_iter()
+            .filter(|word_search| is_word_matching(&grid, word_search))
+            .count();
+        if num_matches == 2 {
+            num_x_shape_mas_matches += 1;
+        }
+    }
+
+    Ok(Some(num_x_shape_mas_matches))
+}
+
+// ----------------------------------------------------
+
+/// Generate a square grid of `size` x `size` letters from `XMAS`.
+pub fn generate_input(size: usize, rng: &mut Rng) -> String {
+    const LETTERS: &[char] = &['X', 'M', 'A', 'S'];
+    let mut input = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        for _ in 0..size {
+            input.push(*rng.choose(LETTERS));
+        }
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    #[test]
+    fn test_simple_parsing() {
+        let parsed = parse_input(EXAMPLE_INPUT).unwrap();
+        dbg!(&parsed);
+        assert_eq!(parsed, Grid::new(vec![
+            vec!['M', 'M', 'M', 'S', 'X', 'X', 'M', 'A', 'S', 'M'],
+            vec!['M', 'S', 'A', 'M', 'X', 'M', 'S', 'M', 'S', 'A'],
+            vec!['A', 'M', 'X', 'S', 'X', 'M', 'A', 'A', 'M', 'M'],
+            vec!['M', 'S', 'A', 'M', 'A', 'S', 'M', 'S', 'M', 'X'],
+            vec!['X', 'M', 'A', 'S', 'A', 'M', 'X', 'A', 'M', 'M'],
+            vec!['X', 'X', 'A', 'M', 'M', 'X', 'X', 'A', 'M', 'A'],
+            vec!['S', 'M', 'S', 'M', 'S', 'A', 'S', 'X', 'S', 'S'],
+            vec!['S', 'A', 'X', 'A', 'M', 'A', 'S', 'A', 'A', 'A'],
+            vec!['M', 'A', 'M', 'M', 'M', 'X', 'M', 'M', 'M', 'M'],
+            vec!['M', 'X', 'M', 'X', 'A', 'X', 'M', 'A', 'S', 'X'],
+        ]).unwrap());
+    }
+
+    #[test]
+    fn test_parsing_invalid_grid() {
+        assert_eq!(parse_input("").unwrap_err().to_string(), "Empty grid");
+        assert_eq!(
+            parse_input("XMAS\nXMA\n").unwrap_err().to_string(),
+            "Line 2 has 3 chars, but grid width is 4 (from first line)",
+        );
+    }
+
+    #[test]
+    fn test_parsing_windows_input() {
+        let input = EXAMPLE_INPUT.replace('\n', "\r\n");
+        assert_eq!(parse_input(&input).unwrap(), parse_input(EXAMPLE_INPUT).unwrap());
+        assert_eq!(solve_part1(&input).unwrap(), Some(18));
+        // Spaces could be cells of a grid, trailing ones aren't ignored (normalizing the input keeps them)
+        assert_eq!(
+            parse_input("XMAS \r\nXMAS\r\n").unwrap_err().to_string(),
+            "Line 2 has 4 chars, but grid width is 5 (from first line)",
+        );
+    }
+
+    #[test]
+    fn test_part1_first_2_lines() {
+        //  x=.. 0123456789
+        // y=0 | MMMSXXMASM
+        // y=1 | MSAMXMSMSA
+        let result = solve_part1("\
+MMMSXXMASM
+MSAMXMSMSA
+").unwrap();
+        assert_eq!(result, Some(2));
+    }
+
+    #[test]
+    fn test_part1() {
+        let result = solve_part1(EXAMPLE_INPUT).unwrap();
+        assert_eq!(result, Some(18));
+    }
+
+    #[test]
+    fn test_part2() {
+        let result = solve_part2(EXAMPLE_INPUT).unwrap();
+        assert_eq!(result, Some(9));
+    }
+
+    #[test]
+    fn test_grid_larger_than_i16() {
+        // Positions past 32767 used to wrap around
+        let width = 40_000;
+        let line = ".".repeat(width - 4) + "XMAS";
+        let grid = parse_input(&format!("{line}\n{line}\n")).unwrap();
+        let matches = WordFinder::new(&["XMAS"]).unwrap().find_all(&grid);
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|found| found.start.x == 39_996 && found.dir == Direction::Right));
+        assert_eq!(grid.get_at(&Pos { x: 39_999, y: 1 }), Some('S'));
+        assert_eq!(grid.get_at(&Pos { x: 40_000, y: 1 }), None);
+
+        let tall_input = "X\nM\nA\nS\n".repeat(10_000);
+        assert_eq!(solve_part1(&tall_input).unwrap(), Some(10_000));
+    }
+
+    #[test]
+    fn test_generated_input() {
+        let input = generate_input(20, &mut Rng::new(42));
+        assert_eq!(input, generate_input(20, &mut Rng::new(42)));
+        let grid = parse_input(&input).unwrap();
+        assert_eq!((grid.width(), grid.height()), (20, 20));
+        assert!(solve_part1(&input).unwrap() > Some(0));
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::strategies;
+
+    const LETTERS: &[char] = &['X', 'M', 'A', 'S'];
+
+    // All lines of the grid as strings: rows, columns, diagonals & anti-diagonals
+    fn all_lines(grid: &[Vec<char>]) -> Vec<String> {
+        let height = grid.len() as i32;
+        let width = grid[0].len() as i32;
+        let line_from = |mut x: i32, mut y: i32, dx: i32, dy: i32| {
+            let mut line = String::new();
+            while 0 <= x && x < width && 0 <= y && y < height {
+                line.push(grid[y as usize][x as usize]);
+                x += dx;
+                y += dy;
+            }
+            line
+        };
+        let mut lines = vec![];
+        for y in 0..height {
+            lines.push(line_from(0, y, 1, 0));
+            lines.push(line_from(0, y, 1, 1));
+            lines.push(line_from(width - 1, y, -1, 1));
+        }
+        for x in 0..width {
+            lines.push(line_from(x, 0, 0, 1));
+            if x > 0 {
+                lines.push(line_from(x, 0, 1, 1));
+            }
+            if x < width - 1 {
+                lines.push(line_from(x, 0, -1, 1));
+            }
+        }
+        lines
+    }
+
+    fn count_overlapping(haystack: &str, needle: &str) -> usize {
+        (0..haystack.len()).filter(|&idx| haystack[idx..].starts_with(needle)).count()
+    }
+
+    fn solve_part1_reference(grid: &[Vec<char>]) -> usize {
+        all_lines(grid).iter()
+            .map(|line| count_overlapping(line, "XMAS") + count_overlapping(line, "SAMX"))
+            .sum()
+    }
+
+    fn solve_part2_reference(grid: &[Vec<char>]) -> usize {
+        let is_mas = |a: char, b: char| matches!((a, b), ('M', 'S') | ('S', 'M'));
+        let mut count = 0;
+        for y in 1..grid.len().saturating_sub(1) {
+            for x in 1..grid[0].len().saturating_sub(1) {
+                if grid[y][x] == 'A'
+                    && is_mas(grid[y - 1][x - 1], grid[y + 1][x + 1])
+                    && is_mas(grid[y - 1][x + 1], grid[y + 1][x - 1])
+                {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_large_generated_grid_matches_reference() {
+        let input = generate_input(400, &mut crate::utils::rng::Rng::new(42));
+        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        assert_eq!(solve_part1(&input).unwrap(), Some(solve_part1_reference(&grid)));
+        assert_eq!(solve_part2(&input).unwrap(), Some(solve_part2_reference(&grid)));
+    }
+
+    proptest! {
+        #[test]
+        fn part1_matches_reference(grid in strategies::letter_grid(LETTERS, 12, 12)) {
+            let input = strategies::grid_to_input(&grid);
+            prop_assert_eq!(solve_part1(&input).unwrap(), Some(solve_part1_reference(&grid)));
+        }
+
+        #[test]
+        fn part2_matches_reference(grid in strategies::letter_grid(LETTERS, 12, 12)) {
+            let input = strategies::grid_to_input(&grid);
+            prop_assert_eq!(solve_part2(&input).unwrap(), Some(solve_part2_reference(&grid)));
+        }
+
+        #[test]
+        fn parsing_roundtrips(grid in strategies::letter_grid(LETTERS, 12, 12)) {
+            let input = strategies::grid_to_input(&grid);
+            let parsed = parse_input(&input).unwrap();
+            prop_assert_eq!(&parsed, &Grid::new(grid).unwrap());
+            prop_assert_eq!(parsed.to_string(), input);
+        }
+
+        #[test]
+        fn transformed_grid_display_roundtrips(grid in strategies::letter_grid(LETTERS, 12, 12)) {
+            let grid = Grid::new(grid).unwrap();
+            for view in [grid.view().rotate_cw(), grid.view().transpose().flip_vertical(), grid.view().rotate_half()] {
+                prop_assert_eq!(parse_input(&view.to_string()).unwrap(), view.to_grid());
+            }
+        }
+    }
+}
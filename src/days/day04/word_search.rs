@@ -0,0 +1,300 @@
+//! Find all the words of a dictionary in a grid, in all 8 directions.
+//!
+//! Each line of the grid (rows, columns & diagonals, read both ways) goes once through an
+//! Aho–Corasick automaton of the dictionary, which gives all the words ending at each char, even
+//! when they overlap or are inside each other.
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{bail, Result as AnyResult};
+
+use crate::utils::grid::{CharGrid, Coord, Direction, Grid, Pos};
+
+/// A word of the dictionary found in the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordMatch {
+    /// Index of the word in the dictionary
+    pub word_idx: usize,
+    /// Position of the first char of the word
+    pub start: Pos,
+    pub dir: Direction,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: HashMap<char, usize>,
+    /// Node of the longest proper suffix of this node's prefix that is also in the trie
+    fail: usize,
+    /// Words ending at this node, including the ones ending at its fail nodes
+    words: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct WordFinder {
+    words: Vec<Vec<char>>,
+    /// Trie of the words, the root is the first node
+    nodes: Vec<Node>,
+}
+
+impl WordFinder {
+    /// NOTE: A word present multiple times in the dictionary is only matched as the first one.
+    pub fn new(words: &[&str]) -> AnyResult<Self> {
+        let mut finder = Self {
+            words: words.iter().map(|word| word.chars().collect()).collect(),
+            nodes: vec![Node::default()],
+        };
+        for word_idx in 0..finder.words.len() {
+            if finder.words[word_idx].is_empty() {
+                bail!("Word #{num} of the dictionary is empty", num = word_idx + 1);
+            }
+            let mut node_idx = 0;
+            for chr_idx in 0..finder.words[word_idx].len() {
+                let chr = finder.words[word_idx][chr_idx];
+                node_idx = match finder.nodes[node_idx].children.get(&chr) {
+                    Some(&child_idx) => child_idx,
+                    None => {
+                        finder.nodes.push(Node::default());
+                        let child_idx = finder.nodes.len() - 1;
+                        finder.nodes[node_idx].children.insert(chr, child_idx);
+                        child_idx
+                    }
+                };
+            }
+            if finder.nodes[node_idx].words.is_empty() {
+                finder.nodes[node_idx].words.push(word_idx);
+            }
+        }
+        finder.link_fail_nodes();
+        Ok(finder)
+    }
+
+    // Breadth-first, so the fail node of a node (always shallower) is done before it
+    fn link_fail_nodes(&mut self) {
+        let mut queue: VecDeque<usize> = self.nodes[0].children.values().copied().collect();
+        while let Some(node_idx) = queue.pop_front() {
+            let children: Vec<(char, usize)> = self.nodes[node_idx].children.iter().map(|(&chr, &idx)| (chr, idx)).collect();
+            for (chr, child_idx) in children {
+                let fail_idx = self.next_node(self.nodes[node_idx].fail, chr);
+                self.nodes[child_idx].fail = fail_idx;
+                let fail_words = self.nodes[fail_idx].words.clone();
+                self.nodes[child_idx].words.extend(fail_words);
+                queue.push_back(child_idx);
+            }
+        }
+    }
+
+    fn next_node(&self, mut node_idx: usize, chr: char) -> usize {
+        loop {
+            if let Some(&child_idx) = self.nodes[node_idx].children.get(&chr) {
+                return child_idx;
+            }
+            if node_idx == 0 {
+                return 0;
+            }
+            node_idx = self.nodes[node_idx].fail;
+        }
+    }
+
+    /// Whether the match is the same chars as another match of the same word read the other way
+    /// (or any other way for a single char), that is counted instead.
+    fn is_duplicate(&self, word_idx: usize, dir: Direction) -> bool {
+        let word = &self.words[word_idx];
+        if word.len() == 1 {
+            return dir != Direction::Right;
+        }
+        let is_palindrome = word.iter().eq(word.iter().rev());
+        let is_forward = matches!(dir, Direction::Right | Direction::BottomRight | Direction::Bottom | Direction::BottomLeft);
+        is_palindrome && !is_forward
+    }
+
+    /// All the occurrences of the words in the grid, in all directions.
+    ///
+    /// Palindromic words are found once (not once per way they can be read), in a direction going
+    /// right or down, and single chars once with `Direction::Right`.
+    pub fn find_all(&self, grid: &Grid) -> Vec<WordMatch> {
+        let mut matches = vec![];
+        for dir in Direction::ALL {
+            let (dx, dy) = dir.delta();
+            // NOTE: A line starts where the previous char would be outside the grid
+            let line_starts = grid.iter_with_position()
+                .map(|(pos, _)| pos)
+                .filter(|pos| grid.get_at(&dir.reverse_dir().next_pos(pos)).is_none());
+            for line_start in line_starts {
+                let mut node_idx = 0;
+                let mut pos = line_start;
+                while let Some(chr) = grid.get_at(&pos) {
+                    node_idx = self.next_node(node_idx, chr);
+                    for &word_idx in &self.nodes[node_idx].words {
+                        if self.is_duplicate(word_idx, dir) {
+                            continue;
+                        }
+                        // NOTE: Can't wrap, the word was found in a line of the grid
+                        let steps_back = (self.words[word_idx].len() - 1) as Coord;
+                        let start = Pos { x: pos.x - dx * steps_back, y: pos.y - dy * steps_back };
+                        matches.push(WordMatch { word_idx, start, dir });
+                    }
+                    pos = dir.next_pos(&pos);
+                }
+            }
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::day04::parse_input;
+
+    fn find_all(words: &[&str], grid: &str) -> Vec<(String, (Coord, Coord), Direction)> {
+        let finder = WordFinder::new(words).unwrap();
+        let mut matches: Vec<_> = finder.find_all(&parse_input(grid).unwrap())
+            .into_iter()
+            .map(|found| (finder.words[found.word_idx].iter().collect::<String>(), (found.start.x, found.start.y), found.dir))
+            .collect();
+        matches.sort_by_key(|(word, start, dir)| (word.clone(), *start, *dir as u8));
+        matches
+    }
+
+    #[test]
+    fn test_all_directions() {
+        let grid = "\
+S..S..S
+.A.A.A.
+..MMM..
+SAMXMAS
+..MMM..
+.A.A.A.
+S..S..S
+";
+        let matches = find_all(&["XMAS"], grid);
+        assert_eq!(matches.len(), 8);
+        let mut dirs: Vec<Direction> = matches.iter().map(|&(_, start, dir)| {
+            assert_eq!(start, (3, 3));
+            dir
+        }).collect();
+        dirs.sort_by_key(|&dir| dir as u8);
+        assert_eq!(dirs, Direction::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_multiple_words() {
+        let matches = find_all(&["XMAS", "MA", "AS", "Z"], "XMAS\n");
+        assert_eq!(matches, vec![
+            ("AS".to_string(), (2, 0), Direction::Right),
+            ("MA".to_string(), (1, 0), Direction::Right),
+            ("XMAS".to_string(), (0, 0), Direction::Right),
+        ]);
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let matches = find_all(&["AA"], "AAA\n");
+        assert_eq!(matches, vec![
+            ("AA".to_string(), (0, 0), Direction::Right),
+            ("AA".to_string(), (1, 0), Direction::Right),
+        ]);
+        // Sharing chars with another word
+        let matches = find_all(&["XMAS", "SAMX"], "XMASAMX\n");
+        assert_eq!(matches, vec![
+            ("SAMX".to_string(), (3, 0), Direction::Left),
+            ("SAMX".to_string(), (3, 0), Direction::Right),
+            ("XMAS".to_string(), (0, 0), Direction::Right),
+            ("XMAS".to_string(), (6, 0), Direction::Left),
+        ]);
+    }
+
+    #[test]
+    fn test_palindromic_words() {
+        // Found once, not once per way to read it
+        assert_eq!(find_all(&["ABA"], "ABA\n"), vec![("ABA".to_string(), (0, 0), Direction::Right)]);
+        assert_eq!(find_all(&["ABA"], "A\nB\nA\n"), vec![("ABA".to_string(), (0, 0), Direction::Bottom)]);
+        assert_eq!(find_all(&["ABA"], "..A\n.B.\nA..\n"), vec![("ABA".to_string(), (2, 0), Direction::BottomLeft)]);
+        assert_eq!(find_all(&["A"], "AB\nBA\n"), vec![
+            ("A".to_string(), (0, 0), Direction::Right),
+            ("A".to_string(), (1, 1), Direction::Right),
+        ]);
+        // Overlapping palindromes
+        assert_eq!(find_all(&["AA"], "AAA\n").len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_dictionary() {
+        assert_eq!(WordFinder::new(&["XMAS", ""]).unwrap_err().to_string(), "Word #2 of the dictionary is empty");
+        // Duplicated words are only matched once
+        let finder = WordFinder::new(&["XMAS", "XMAS"]).unwrap();
+        let matches = finder.find_all(&parse_input("XMAS\n").unwrap());
+        assert_eq!(matches, vec![WordMatch { word_idx: 0, start: Pos { x: 0, y: 0 }, dir: Direction::Right }]);
+        // Nothing to find
+        assert_eq!(WordFinder::new(&[]).unwrap().find_all(&parse_input("XMAS\n").unwrap()), vec![]);
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::strategies;
+
+    const LETTERS: &[char] = &['X', 'M', 'A', 'S'];
+
+    // Try each word from each position in each direction
+    fn find_all_reference(words: &[String], grid: &Grid) -> Vec<WordMatch> {
+        let mut matches = vec![];
+        for (word_idx, word) in words.iter().enumerate() {
+            if words[..word_idx].contains(word) {
+                continue;
+            }
+            let is_palindrome = word.chars().eq(word.chars().rev());
+            for (start, _) in grid.iter_with_position() {
+                for dir in Direction::ALL {
+                    let mut pos = start;
+                    let is_match = word.chars().all(|chr| {
+                        let found = grid.get_at(&pos) == Some(chr);
+                        pos = dir.next_pos(&pos);
+                        found
+                    });
+                    if !is_match {
+                        continue;
+                    }
+                    // Keep a single match for the same chars read another way
+                    let (dx, dy) = dir.delta();
+                    let end = Pos {
+                        x: start.x + dx * (word.chars().count() as Coord - 1),
+                        y: start.y + dy * (word.chars().count() as Coord - 1),
+                    };
+                    let is_kept = if word.chars().count() == 1 {
+                        dir == Direction::Right
+                    } else if is_palindrome {
+                        (start.y, start.x) < (end.y, end.x)
+                    } else {
+                        true
+                    };
+                    if is_kept {
+                        matches.push(WordMatch { word_idx, start, dir });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    fn sorted(mut matches: Vec<WordMatch>) -> Vec<WordMatch> {
+        matches.sort_by_key(|found| (found.word_idx, found.start.y, found.start.x, found.dir as u8));
+        matches
+    }
+
+    proptest! {
+        #[test]
+        fn find_all_matches_reference(
+            grid in strategies::letter_grid(LETTERS, 8, 8),
+            words in prop::collection::vec("[XMAS]{1,4}", 1..5),
+        ) {
+            let grid = Grid::new(grid).unwrap();
+            let word_refs: Vec<&str> = words.iter().map(String::as_str).collect();
+            let matches = WordFinder::new(&word_refs).unwrap().find_all(&grid);
+            prop_assert_eq!(sorted(matches), sorted(find_all_reference(&words, &grid)));
+        }
+    }
+}
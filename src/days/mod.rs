@@ -6,7 +6,19 @@ mod day04;
 // --- Template
 // mod day00;
 
-pub type PartFn = fn(&str) -> Option<usize>;
+use anyhow::Result as AnyResult;
+
+use crate::utils::rng::Rng;
+
+/// Solve a part, `Ok(None)` when not implemented yet
+pub type PartFn = fn(&str) -> AnyResult<Option<usize>>;
+/// Parse the input, only to check it's valid (e.g. for fuzzing)
+pub type ParseFn = fn(&str) -> AnyResult<()>;
+/// Explain the results of a day on an input, for debugging (e.g. why each item counts or not),
+/// using ANSI colors only when told so (e.g. when printed to a terminal)
+pub type ExplainFn = fn(&str, bool) -> AnyResult<String>;
+/// Generate a valid input of the given size (its meaning depends on the day)
+pub type GenerateFn = fn(usize, &mut Rng) -> String;
 
 pub struct Day {
     pub name: &'static str,
@@ -14,38 +26,49 @@ pub struct Day {
     pub part1: Part,
     pub part2: Part,
     pub default_input: &'static str,
+    pub parse_input: ParseFn,
+    pub generate_input: GenerateFn,
+    /// Only for days having an explain mode
+    pub explain: Option<ExplainFn>,
 }
 
 pub struct Part {
     pub func: PartFn,
-    pub expected: Option<usize>,
 }
 
+// NOTE: Expected answers are not here, they depend on the input, see `inputs/answers.toml`.
 macro_rules! def_day {
-    ($d: ident, desc=$desc: tt, expected=($exp1: expr, $exp2: expr)) => {
+    ($d: ident, desc=$desc: tt) => {
+        def_day!(@day $d, $desc, None)
+    };
+    ($d: ident, desc=$desc: tt, explain) => {
+        def_day!(@day $d, $desc, Some($d::explain_input))
+    };
+    (@day $d: ident, $desc: tt, $explain: expr) => {
         Day {
             name: stringify!($d),
             description: $desc,
             part1: Part {
                 func: $d::solve_part1,
-                expected: $exp1,
             },
             part2: Part {
                 func: $d::solve_part2,
-                expected: $exp2,
             },
             default_input: include_str!(concat!("../../inputs/", stringify!($d), ".txt")),
+            parse_input: |input| $d::parse_input(input).map(|_| ()),
+            generate_input: $d::generate_input,
+            explain: $explain,
         }
     };
 }
 
 pub static DAYS: &[Day] = &[
-    def_day!(day01, desc="Historian Hysteria", expected=(Some(1834060), Some(21607792))),
-    def_day!(day02, desc="Red-Nosed Reports", expected=(Some(314), Some(373))),
-    def_day!(day03, desc="Mull It Over", expected=(Some(166905464), Some(72948684))),
-    def_day!(day04, desc="Ceres Search", expected=(Some(2504), Some(1923))),
+    def_day!(day01, desc="Historian Hysteria"),
+    def_day!(day02, desc="Red-Nosed Reports", explain),
+    def_day!(day03, desc="Mull It Over", explain),
+    def_day!(day04, desc="Ceres Search"),
 
     // --- Template
-    // def_day!(day00, desc="DESCRIPTION", expected=(None, None)),
+    // def_day!(day00, desc="DESCRIPTION"),
 ];
 
@@ -0,0 +1,346 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use anyhow::{Context, Result as AnyResult};
+
+use crate::answers::input_hash;
+use crate::days::Day;
+use crate::utils::rng::Rng;
+
+// NOTE: This is a simple mutation-based fuzzer (no coverage guidance), but it doesn't need any
+//   network, nightly toolchain or external tool, and it's good enough to find inputs making our
+//   parsers & solutions panic instead of returning an error.
+
+/// Max size of a fuzzed input, bigger inputs are only slower to run, not more interesting
+const MAX_INPUT_LEN: usize = 4096;
+
+/// Tokens that are likely to trigger edge cases when inserted in an input
+const INTERESTING_TOKENS: &[&str] = &[
+    "\n", "\n\n", "\r\n", " ", "   ", "\t", ",", "-", "+", "0", "1", "-1",
+    "127", "128", "255", "256", "32767", "32768", "65535", "65536",
+    "2147483647", "2147483648", "-2147483648", "-2147483649", "4294967295", "4294967296",
+    "9223372036854775807", "18446744073709551615", "18446744073709551616", "99999999999999999999",
+    "mul(", "mul(1,2)", "mul(999,999)", ")", "do()", "don't()", "XMAS", "MAS", "X", "é", "💥",
+];
+
+fn corpus_dir(day: &Day) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus").join(day.name)
+}
+
+fn regressions_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions")
+}
+
+/// Read all inputs of a directory (if it exists), sorted by file name to be deterministic
+fn read_inputs(dir: &Path) -> AnyResult<Vec<(PathBuf, String)>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to list {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+    paths.into_iter()
+        .map(|path| {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            Ok((path, content))
+        })
+        .collect()
+}
+
+// --------------------------------------------------------
+
+/// A function of a day that must never panic, whatever the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Parse,
+    Part1,
+    Part2,
+}
+impl Target {
+    pub const ALL: [Target; 3] = [Target::Parse, Target::Part1, Target::Part2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Parse => "parse",
+            Target::Part1 => "part1",
+            Target::Part2 => "part2",
+        }
+    }
+
+    /// Run the target on the input, returns whether it succeeded
+    fn run(self, day: &Day, input: &str) -> bool {
+        match self {
+            Target::Parse => (day.parse_input)(input).is_ok(),
+            Target::Part1 => (day.part1.func)(input).is_ok(),
+            Target::Part2 => (day.part2.func)(input).is_ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Crash {
+    pub day_name: &'static str,
+    pub target: Target,
+    pub message: String,
+    pub input: String,
+}
+
+thread_local! {
+    static SILENCE_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Install (once) a panic hook that doesn't print anything for panics we expect to catch
+fn install_silencing_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !SILENCE_PANICS.get() {
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+/// Run the target, catching (silently) any panic.
+/// Returns the panic message on panic, or whether it succeeded otherwise.
+fn run_catching(target: Target, day: &Day, input: &str) -> Result<bool, String> {
+    // NOTE: The panic hook is global, so we silence panics per thread instead of swapping hooks,
+    //   which would race with other threads (e.g. tests running in parallel).
+    install_silencing_panic_hook();
+    SILENCE_PANICS.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| target.run(day, input)));
+    SILENCE_PANICS.set(false);
+    result.map_err(|payload| {
+        if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "<unknown panic payload>".to_string()
+        }
+    })
+}
+
+/// Run all targets of the day on the input, returns the crashes (if any) and whether the input
+/// was parsed successfully.
+pub fn check_input(day: &Day, input: &str) -> (Vec<Crash>, bool) {
+    let mut crashes = vec![];
+    let mut parsed_ok = false;
+    for target in Target::ALL {
+        match run_catching(target, day, input) {
+            Ok(success) => {
+                if target == Target::Parse {
+                    parsed_ok = success;
+                }
+            }
+            Err(message) => crashes.push(Crash {
+                day_name: day.name,
+                target,
+                message,
+                input: input.to_string(),
+            }),
+        }
+    }
+    (crashes, parsed_ok)
+}
+
+// --------------------------------------------------------
+
+/// Apply one random mutation to the input.
+fn mutate_once(input: &mut Vec<u8>, corpus: &[String], rng: &mut Rng) {
+    let pos = |rng: &mut Rng, input: &Vec<u8>| rng.gen_index(input.len() + 1);
+    match rng.gen_range(0..7) {
+        // Replace a byte by a random printable one
+        0 if !input.is_empty() => {
+            let idx = rng.gen_index(input.len());
+            input[idx] = rng.gen_range(0x20..0x7f) as u8;
+        }
+        // Insert an interesting token
+        1 | 2 => {
+            let idx = pos(rng, input);
+            let token = rng.choose(INTERESTING_TOKENS);
+            input.splice(idx..idx, token.bytes());
+        }
+        // Delete a range
+        3 if !input.is_empty() => {
+            let start = rng.gen_index(input.len());
+            let end = (start + 1 + rng.gen_index(8)).min(input.len());
+            input.drain(start..end);
+        }
+        // Duplicate a range
+        4 if !input.is_empty() => {
+            let start = rng.gen_index(input.len());
+            let end = (start + 1 + rng.gen_index(32)).min(input.len());
+            let chunk = input[start..end].to_vec();
+            let idx = pos(rng, input);
+            input.splice(idx..idx, chunk);
+        }
+        // Splice a part of another corpus entry
+        5 if !corpus.is_empty() => {
+            let other = rng.choose(corpus).as_bytes();
+            if !other.is_empty() {
+                let start = rng.gen_index(other.len());
+                let end = (start + 1 + rng.gen_index(64)).min(other.len());
+                let idx = pos(rng, input);
+                input.splice(idx..idx, other[start..end].iter().copied());
+            }
+        }
+        // Truncate
+        _ => {
+            let len = pos(rng, input);
+            input.truncate(len);
+        }
+    }
+}
+
+/// Derive a new input from the given one, with a few random mutations
+pub fn mutate(input: &str, corpus: &[String], rng: &mut Rng) -> String {
+    let mut bytes = input.as_bytes().to_vec();
+    for _ in 0..1 + rng.gen_index(4) {
+        mutate_once(&mut bytes, corpus, rng);
+    }
+    bytes.truncate(MAX_INPUT_LEN);
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Fuzz all targets of the day for `num_runs` mutated inputs, starting from the day's corpus.
+///
+/// Mutated inputs that still parse are added to the (in memory) corpus, to go deeper in the
+/// parts. Crashing inputs should be saved with `save_regression`, so tests replay them.
+pub fn fuzz_day(day: &Day, num_runs: usize, rng: &mut Rng) -> AnyResult<Vec<Crash>> {
+    let mut corpus: Vec<String> = read_inputs(&corpus_dir(day))?
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
+    if corpus.is_empty() {
+        // No seeds for this day (yet), a small generated input is better than nothing
+        corpus.push((day.generate_input)(5, rng));
+    }
+    let mut seen: HashSet<String> = corpus.iter().cloned().collect();
+
+    let mut crashes: Vec<Crash> = vec![];
+    for seed_input in corpus.clone() {
+        crashes.extend(check_input(day, &seed_input).0);
+    }
+    for _ in 0..num_runs {
+        let base = rng.choose(&corpus).clone();
+        let input = mutate(&base, &corpus, rng);
+        if !seen.insert(input.clone()) {
+            continue; // already tried
+        }
+        let (new_crashes, parsed_ok) = check_input(day, &input);
+        if parsed_ok && new_crashes.is_empty() {
+            corpus.push(input);
+        }
+        for crash in new_crashes {
+            // Only keep the first crash per target & message, the rest are likely the same bug
+            let is_known = crashes.iter()
+                .any(|known| known.target == crash.target && known.message == crash.message);
+            if !is_known {
+                crashes.push(crash);
+            }
+        }
+    }
+
+    Ok(crashes)
+}
+
+/// Save the crash input, returns the path of the reproducer
+pub fn save_regression(crash: &Crash) -> AnyResult<PathBuf> {
+    let dir = regressions_dir().join(crash.day_name);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{target}-{hash}.txt", target = crash.target.name(), hash = input_hash(&crash.input)));
+    fs::write(&path, &crash.input).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::days::DAYS;
+
+    use super::*;
+
+    /// All saved crash reproducers, with the day they're for (from their directory name)
+    fn load_regressions() -> AnyResult<Vec<(String, PathBuf, String)>> {
+        let mut regressions = vec![];
+        for day_dir in read_dirs(&regressions_dir())? {
+            let day_name = day_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            for (path, input) in read_inputs(&day_dir)? {
+                regressions.push((day_name.clone(), path, input));
+            }
+        }
+        Ok(regressions)
+    }
+
+    /// Sub-directories of a directory (if it exists), sorted by name
+    fn read_dirs(dir: &Path) -> AnyResult<Vec<PathBuf>> {
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to list {}", dir.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        dirs.retain(|path| path.is_dir());
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    #[test]
+    fn test_mutate_is_deterministic() {
+        let corpus = vec!["1 2 3\n4 5 6\n".to_string()];
+        let mutated = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..20).map(|_| mutate(&corpus[0], &corpus, &mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(mutated(42), mutated(42));
+        assert!(mutated(42).iter().any(|input| *input != corpus[0]));
+    }
+
+    #[test]
+    fn test_all_days_have_a_corpus() {
+        for day in DAYS {
+            let corpus = read_inputs(&corpus_dir(day)).unwrap();
+            assert!(!corpus.is_empty(), "no fuzz corpus for {}", day.name);
+            for (path, input) in corpus {
+                let (crashes, parsed_ok) = check_input(day, &input);
+                assert!(crashes.is_empty(), "corpus input {} crashes: {crashes:?}", path.display());
+                assert!(parsed_ok, "corpus input {} doesn't parse", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_regressions() {
+        for (day_name, path, input) in load_regressions().unwrap() {
+            let day = DAYS.iter().find(|day| day.name == day_name)
+                .unwrap_or_else(|| panic!("unknown day for regression {}", path.display()));
+            let (crashes, _) = check_input(day, &input);
+            assert!(crashes.is_empty(), "regression {} still crashes: {crashes:?}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_short_fuzz_run() {
+        // NOTE: Not saving crashes here, a short run must just not find anything
+        for day in DAYS {
+            let corpus: Vec<String> = read_inputs(&corpus_dir(day)).unwrap()
+                .into_iter().map(|(_, input)| input).collect();
+            let mut rng = Rng::new(42);
+            for _ in 0..50 {
+                let base = rng.choose(&corpus).clone();
+                let input = mutate(&base, &corpus, &mut rng);
+                let (crashes, _) = check_input(day, &input);
+                assert!(crashes.is_empty(), "{} crashes on {input:?}: {crashes:?}", day.name);
+            }
+        }
+    }
+}
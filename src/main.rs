@@ -1,52 +1,99 @@
 use std::env;
+use std::fs;
 use std::process::exit;
 
-use anyhow::Result as AnyResult;
+use anyhow::{Context, Result as AnyResult};
 
+mod alloc_stats;
+mod answers;
+mod bench;
 mod days;
-use days::{Part, Day, DAYS};
+mod fuzz;
+mod runner;
+mod utils;
+mod watch;
+use answers::Answers;
+use days::{Day, DAYS};
+use runner::{run_day, InputMode};
+use utils::rng::Rng;
 
 fn print_usage() {
     let prog_name = env::args().next().unwrap_or("prog".to_string());
     let day_names: Vec<_> = DAYS.iter().map(|d| d.name).collect();
     println!("Usage:");
     println!("  {} <cmd>", prog_name);
-    println!("  {} <day> [<custom_input_path>]", prog_name);
-    println!("");
+    println!("  {} all [--parallel] [--jobs <N>]", prog_name);
+    println!("  {} <day> [--explain] [--raw-input] [<custom_input_path>]", prog_name);
+    println!("  {} record <day> [--raw-input] [<custom_input_path>]", prog_name);
+    println!("  {} watch [<day>]", prog_name);
+    println!("  {} bench [<day>] [--runs <N>] [--compare] [--against <commit>] [--threshold <percent>]", prog_name);
+    println!("  {} generate <day> [--size <N>] [--seed <S>]", prog_name);
+    println!("  {} fuzz [<day>] [--runs <N>] [--seed <S>]", prog_name);
+    println!("  {} hint <day> <part1|part2> <wrong|too-high|too-low> <value> [--raw-input] [<custom_input_path>]", prog_name);
+    println!();
 
     println!("<cmd> can be:");
-    println!("  all   - run all available days");
-    println!("  last  - run the last available day (used while dev)");
-    println!("  list  - list available days");
-    println!("");
+    println!("  all      - run all available days (optionally on a thread pool, see --parallel)");
+    println!("  last     - run the last available day (used while dev)");
+    println!("  list     - list available days");
+    println!("  record   - run a day and save its results as the expected answers for its input");
+    println!("  bench    - benchmark days (default: all), save results & compare them with another commit");
+    println!("  watch    - re-run a day (default: last) and its examples on source or input changes");
+    println!("  generate - print a random (but deterministic for a seed) input for a day, for stress tests");
+    println!("  fuzz     - feed mutated inputs to days' parse & parts to find panics, saving reproducers");
+    println!("  hint     - save what we learned from a wrong answer, to flag it before submitting again");
+    println!();
 
     let joined_days = day_names.join(", ");
     println!("<day> can be one of: {}", joined_days);
-    println!("");
+    println!();
 }
 
-fn run_part(part_name: &str, part: &Part, input: &str) {
-    let result = (part.func)(input);
-    match (result, part.expected) {
-        (None, _) => {
-            println!("❌ {part_name}: Not implemented");
-        }
-        (Some(value), Some(expected)) => {
-            if value == expected {
-                println!("✅ {part_name}: {value:?} (== expected)");
-            } else {
-                println!("❌ {part_name}: Expected {expected} but got {value} !!");
-            }
+fn find_day(wanted_day: &str) -> &'static Day {
+    let matching_day = DAYS.iter().find(|day| day.name == wanted_day);
+    match matching_day {
+        Some(day) => day,
+        None => {
+            println!("Unknown day '{}'", wanted_day);
+            exit(1);
         }
-        (Some(value), None) => eprintln!("-- {part_name}: {value} ?"),
+    }
+}
+
+fn load_input(day: &Day, custom_input_path: Option<&String>, mode: InputMode) -> AnyResult<String> {
+    let input = match custom_input_path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read input file {path}"))?,
+        None => day.default_input.to_string(),
     };
+    Ok(runner::prepare_input(input, mode))
 }
 
-fn run_day(day: &Day) -> AnyResult<()> {
-    println!("=>> {name} - {desc}", name=day.name, desc=day.description);
-    run_part("Part1", &day.part1, &day.default_input);
-    run_part("Part2", &day.part2, &day.default_input);
-    Ok(())
+fn record_day(day: &Day, input: &str, input_name: &str, answers: &mut Answers) -> AnyResult<()> {
+    println!("=>> Recording answers of {name} for input {input_name}", name=day.name);
+    let entry = answers.entry(day.name, input);
+    entry.input = Some(input_name.to_string());
+    for (part_num, part) in [(1, &day.part1), (2, &day.part2)] {
+        let part_name = format!("Part{part_num}");
+        let Some(value) = (part.func)(input).with_context(|| format!("{part_name} failed"))? else {
+            println!("-- {part_name}: Not implemented, nothing to record");
+            continue;
+        };
+        let part_answer = entry.part_mut(part_num);
+        match part_answer.answer {
+            Some(previous) if previous == value => {
+                println!("-- {part_name}: {value} (already recorded)");
+            }
+            Some(previous) => {
+                println!("⚠️ {part_name}: {value} (replaces previously recorded {previous})");
+            }
+            None => {
+                println!("✅ {part_name}: {value} (recorded)");
+            }
+        }
+        part_answer.answer = Some(value);
+    }
+    answers.save()
 }
 
 fn main() -> anyhow::Result<()> {
@@ -56,12 +103,36 @@ fn main() -> anyhow::Result<()> {
 
     match first_arg.map(String::as_str) {
         Some("all") => {
-            for day in DAYS {
-                run_day(day)?;
+            let answers = Answers::load()?;
+            let mut parallel = false;
+            let mut num_jobs = runner::default_num_jobs();
+            let mut opts = prog_args.iter().skip(2);
+            while let Some(opt) = opts.next() {
+                match opt.as_str() {
+                    "--parallel" => parallel = true,
+                    "--jobs" => {
+                        let Some(value) = opts.next() else {
+                            anyhow::bail!("Missing value for --jobs");
+                        };
+                        num_jobs = value.parse().with_context(|| format!("Invalid number of jobs '{value}'"))?;
+                        parallel = true;
+                    }
+                    _ => anyhow::bail!("Unknown option '{opt}' for all"),
+                }
+            }
+
+            if parallel {
+                runner::run_days_parallel(DAYS, &answers, num_jobs)?;
+            } else {
+                for day in DAYS {
+                    run_day(day, &runner::default_input(day), &answers)?;
+                }
             }
         }
         Some("last") => {
-            run_day(&DAYS.last().unwrap())?;
+            let answers = Answers::load()?;
+            let day = DAYS.last().unwrap();
+            run_day(day, &runner::default_input(day), &answers)?;
         }
         Some("list") => {
             println!("Available days:");
@@ -71,15 +142,208 @@ fn main() -> anyhow::Result<()> {
                 //       (need to return proper enum with all statuses)
             }
         }
-        Some(wanted_day) => {
-            let matching_day = DAYS.iter().find(|day| day.name == wanted_day);
-            match matching_day {
-                Some(day) => run_day(day)?,
-                None => {
-                    println!("Unknown day '{}'", wanted_day);
-                    exit(1);
+        Some("watch") => {
+            let day = match prog_args.get(2) {
+                Some(wanted_day) => find_day(wanted_day),
+                None => DAYS.last().unwrap(),
+            };
+            watch::watch(day.name)?;
+        }
+        Some("bench") => {
+            let mut wanted_days: Vec<&Day> = vec![];
+            let mut num_runs = 10;
+            let mut compare = false;
+            let mut against: Option<String> = None;
+            let mut threshold_percent = 20.0;
+            let mut opts = prog_args.iter().skip(2);
+            while let Some(opt) = opts.next() {
+                let mut opt_value = || opts.next().with_context(|| format!("Missing value for {opt}"));
+                match opt.as_str() {
+                    "--runs" => num_runs = opt_value()?.parse().context("Invalid number of runs")?,
+                    "--compare" => compare = true,
+                    "--against" => {
+                        against = Some(opt_value()?.clone());
+                        compare = true;
+                    }
+                    "--threshold" => {
+                        threshold_percent = opt_value()?.parse().context("Invalid threshold")?;
+                    }
+                    wanted_day => wanted_days.push(find_day(wanted_day)),
+                }
+            }
+            anyhow::ensure!(num_runs > 0, "Need at least 1 run");
+            if wanted_days.is_empty() {
+                wanted_days = DAYS.iter().collect();
+            }
+            if cfg!(debug_assertions) {
+                println!("⚠️ Benchmarking a debug build, use `cargo run --release` for meaningful timings");
+            }
+
+            let mut results = bench::BenchResults::new();
+            for day in wanted_days {
+                results.insert(day.name.to_string(), bench::bench_day(day, num_runs));
+            }
+
+            let commit = bench::current_commit()?;
+            let mut history = bench::BenchHistory::load()?;
+            let mut num_regressions = 0;
+            if compare {
+                let baseline_commit = match against {
+                    Some(commit) => commit,
+                    None => history.latest_other_commit(&commit)
+                        .context("No other commit in bench history to compare with")?
+                        .to_string(),
+                };
+                let baseline = history.commits.get(&baseline_commit)
+                    .with_context(|| format!("No bench results for commit {baseline_commit}"))?;
+                let comparisons = bench::compare_results(baseline, &results);
+                num_regressions = bench::print_comparisons(&baseline_commit, &comparisons, threshold_percent / 100.0);
+            }
+            history.record(&commit, &results);
+            history.save()?;
+            println!("Results saved for commit {commit}");
+
+            if num_regressions > 0 {
+                println!("❌ {num_regressions} performance regression(s) !!");
+                exit(1);
+            }
+        }
+        Some("generate") => {
+            let Some(wanted_day) = prog_args.get(2) else {
+                print_usage();
+                exit(1);
+            };
+            let day = find_day(wanted_day);
+            let mut size = 1000;
+            let mut seed = 42;
+            let mut opts = prog_args.iter().skip(3);
+            while let Some(opt) = opts.next() {
+                let value = opts.next().with_context(|| format!("Missing value for {opt}"))?;
+                match opt.as_str() {
+                    "--size" => size = value.parse().with_context(|| format!("Invalid size '{value}'"))?,
+                    "--seed" => seed = value.parse().with_context(|| format!("Invalid seed '{value}'"))?,
+                    _ => anyhow::bail!("Unknown option '{opt}' for generate"),
                 }
+            }
+            anyhow::ensure!(size > 0, "Need a size of at least 1");
+            let mut rng = Rng::new(seed);
+            print!("{}", (day.generate_input)(size, &mut rng));
+        }
+        Some("fuzz") => {
+            let mut wanted_days: Vec<&Day> = vec![];
+            let mut num_runs = 10_000;
+            let mut seed = 42;
+            let mut opts = prog_args.iter().skip(2);
+            while let Some(opt) = opts.next() {
+                let mut opt_value = || opts.next().with_context(|| format!("Missing value for {opt}"));
+                match opt.as_str() {
+                    "--runs" => num_runs = opt_value()?.parse().context("Invalid number of runs")?,
+                    "--seed" => seed = opt_value()?.parse().context("Invalid seed")?,
+                    wanted_day => wanted_days.push(find_day(wanted_day)),
+                }
+            }
+            if wanted_days.is_empty() {
+                wanted_days = DAYS.iter().collect();
+            }
+
+            let mut num_crashes = 0;
+            for day in wanted_days {
+                let mut rng = Rng::new(seed);
+                let crashes = fuzz::fuzz_day(day, num_runs, &mut rng)?;
+                if crashes.is_empty() {
+                    println!("✅ {name}: no crash in {num_runs} runs", name=day.name);
+                }
+                for crash in &crashes {
+                    let path = fuzz::save_regression(crash)?;
+                    println!(
+                        "❌ {name} {target}: {message} (reproducer: {path})",
+                        name=day.name,
+                        target=crash.target.name(),
+                        message=crash.message,
+                        path=path.display(),
+                    );
+                }
+                num_crashes += crashes.len();
+            }
+            if num_crashes > 0 {
+                exit(1);
+            }
+        }
+        Some("record") => {
+            let Some(wanted_day) = prog_args.get(2) else {
+                print_usage();
+                exit(1);
             };
+            let day = find_day(wanted_day);
+            let mut input_mode = InputMode::default();
+            let mut custom_input_path = None;
+            for arg in prog_args.iter().skip(3) {
+                match arg.as_str() {
+                    "--raw-input" => input_mode = InputMode::Raw,
+                    _ => custom_input_path = Some(arg),
+                }
+            }
+            let input = load_input(day, custom_input_path, input_mode)?;
+            let input_name = match custom_input_path {
+                Some(path) => path.clone(),
+                None => format!("inputs/{}.txt", day.name),
+            };
+            let mut answers = Answers::load()?;
+            record_day(day, &input, &input_name, &mut answers)?;
+        }
+        Some("hint") => {
+            let (Some(wanted_day), Some(part_arg), Some(hint_arg), Some(value_arg)) =
+                (prog_args.get(2), prog_args.get(3), prog_args.get(4), prog_args.get(5))
+            else {
+                print_usage();
+                exit(1);
+            };
+            let day = find_day(wanted_day);
+            let part_num = match part_arg.as_str() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => anyhow::bail!("Unknown part '{part_arg}', expected part1 or part2"),
+            };
+            let hint = hint_arg.parse()?;
+            let value: usize = value_arg.parse()
+                .with_context(|| format!("Invalid value '{value_arg}'"))?;
+            let mut input_mode = InputMode::default();
+            let mut custom_input_path = None;
+            for arg in prog_args.iter().skip(6) {
+                match arg.as_str() {
+                    "--raw-input" => input_mode = InputMode::Raw,
+                    _ => custom_input_path = Some(arg),
+                }
+            }
+            let input = load_input(day, custom_input_path, input_mode)?;
+
+            let mut answers = Answers::load()?;
+            let part_answer = answers.entry(day.name, &input).part_mut(part_num);
+            part_answer.add_hint(hint, value);
+            println!("Hints for {name} part{part_num}:", name=day.name);
+            println!("- known wrong: {:?}", part_answer.wrong);
+            println!("- known bounds: {}", part_answer.bounds_description());
+            answers.save()?;
+        }
+        Some(wanted_day) => {
+            let day = find_day(wanted_day);
+            let mut explain = false;
+            let mut input_mode = InputMode::default();
+            let mut custom_input_path = None;
+            for arg in prog_args.iter().skip(2) {
+                match arg.as_str() {
+                    "--explain" => explain = true,
+                    "--raw-input" => input_mode = InputMode::Raw,
+                    _ => custom_input_path = Some(arg),
+                }
+            }
+            anyhow::ensure!(!explain || day.explain.is_some(), "No explain mode for {}", day.name);
+            let input = load_input(day, custom_input_path, input_mode)?;
+            let answers = Answers::load()?;
+            run_day(day, &input, &answers)?;
+            if explain {
+                runner::explain_day(day, &input)?;
+            }
         }
         None => {
             print_usage();
@@ -0,0 +1,189 @@
+use std::borrow::Cow;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result as AnyResult};
+
+use crate::alloc_stats::{self, AllocStats};
+use crate::answers::{Answers, HintCheck, PartAnswer};
+use crate::days::{Day, Part};
+use crate::utils::parsing::normalize_input;
+
+/// How an input file is given to the days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// With `\n` line endings and a single final newline (see `normalize_input`)
+    #[default]
+    Normalized,
+    /// As read, to check how the days handle messy inputs
+    Raw,
+}
+
+/// Prepare an input read from a file for the days, according to the mode.
+///
+/// NOTE: Answers are recorded for the prepared input, so a copy of an input saved on Windows
+/// gets the answers of the original.
+pub fn prepare_input(input: String, mode: InputMode) -> String {
+    match mode {
+        InputMode::Normalized => match normalize_input(&input) {
+            Cow::Borrowed(_) => input,
+            Cow::Owned(normalized) => normalized,
+        },
+        InputMode::Raw => input,
+    }
+}
+
+/// The day's default input, normalized like input files are by default, so all commands run the
+/// days on the same input (and find the same recorded answers).
+pub fn default_input(day: &Day) -> Cow<'static, str> {
+    normalize_input(day.default_input)
+}
+
+/// Result of one execution of a part.
+#[derive(Debug)]
+pub struct PartRun {
+    pub result: AnyResult<Option<usize>>,
+    pub duration: Duration,
+    /// Only available with the `alloc-stats` feature
+    pub alloc: Option<AllocStats>,
+}
+
+pub fn execute_part(part: &Part, input: &str) -> PartRun {
+    let start = Instant::now();
+    let (result, alloc) = alloc_stats::measure(|| (part.func)(input));
+    let duration = start.elapsed();
+    PartRun { result, duration, alloc }
+}
+
+pub fn print_part_run(part_name: &str, run: &PartRun, known: Option<&PartAnswer>) {
+    let expected = known.and_then(|known| known.answer);
+    match (&run.result, expected) {
+        (Err(err), _) => {
+            println!("❌ {part_name}: Failed: {err:#}");
+        }
+        (Ok(None), _) => {
+            println!("❌ {part_name}: Not implemented");
+        }
+        (&Ok(Some(value)), Some(expected)) => {
+            if value == expected {
+                println!("✅ {part_name}: {value:?} (== expected)");
+            } else {
+                println!("❌ {part_name}: Expected {expected} but got {value} !!");
+            }
+        }
+        (&Ok(Some(value)), None) => {
+            // Answer not known yet, use hints from previous wrong answers (if any)
+            match known.filter(|known| known.has_hints()) {
+                None => eprintln!("-- {part_name}: {value} ?"),
+                Some(known) => {
+                    let bounds = known.bounds_description();
+                    match known.check_hints(value) {
+                        HintCheck::KnownWrong => {
+                            println!("❌ {part_name}: {value} is already known wrong !!");
+                        }
+                        HintCheck::OutOfBounds => {
+                            println!("❌ {part_name}: {value} is outside known bounds ({bounds}) !!");
+                        }
+                        HintCheck::Plausible => {
+                            eprintln!("-- {part_name}: {value} ? (within known bounds: {bounds})");
+                        }
+                    }
+                }
+            }
+        }
+    };
+    if let Some(alloc) = run.alloc {
+        println!("   ↳ {alloc}");
+    }
+}
+
+fn print_day_header(day: &Day) {
+    println!("=>> {name} - {desc}", name=day.name, desc=day.description);
+}
+
+pub fn run_part(part_name: &str, part: &Part, input: &str, known: Option<&PartAnswer>) {
+    let run = execute_part(part, input);
+    print_part_run(part_name, &run, known);
+}
+
+pub fn run_day(day: &Day, input: &str, answers: &Answers) -> AnyResult<()> {
+    print_day_header(day);
+    run_part("Part1", &day.part1, input, answers.part(day.name, input, 1));
+    run_part("Part2", &day.part2, input, answers.part(day.name, input, 2));
+    Ok(())
+}
+
+/// Print the day's explanation of its results on the input (for days having an explain mode).
+///
+/// NOTE: Colors are only used when printing to a terminal, so a redirected explanation doesn't
+///   contain escape codes.
+pub fn explain_day(day: &Day, input: &str) -> AnyResult<()> {
+    let explain = day.explain.with_context(|| format!("No explain mode for {}", day.name))?;
+    let explanation = explain(input, io::stdout().is_terminal())?;
+    println!();
+    print!("{explanation}");
+    Ok(())
+}
+
+// --------------------------------------------------------
+
+pub fn default_num_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Run all parts of all given days (on their default input) on a pool of `num_jobs` threads.
+///
+/// Results are printed in day order once everything ran, with a summary of the total time spent
+/// in parts (≈ CPU time, parts are single-threaded) versus the wall time.
+pub fn run_days_parallel(days: &[Day], answers: &Answers, num_jobs: usize) -> AnyResult<()> {
+    // One job per part, so both parts of a day can run at the same time
+    let jobs: Vec<(usize, u8)> = (0..days.len())
+        .flat_map(|day_idx| [(day_idx, 1), (day_idx, 2)])
+        .collect();
+    let next_job_idx = AtomicUsize::new(0);
+    let finished_jobs: Mutex<Vec<Option<PartRun>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+
+    let inputs: Vec<Cow<str>> = days.iter().map(default_input).collect();
+
+    let num_threads = num_jobs.clamp(1, jobs.len().max(1));
+    let wall_start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..num_threads {
+            scope.spawn(|| loop {
+                let job_idx = next_job_idx.fetch_add(1, Ordering::Relaxed);
+                let Some(&(day_idx, part_num)) = jobs.get(job_idx) else {
+                    break; // no more jobs
+                };
+                let day = &days[day_idx];
+                let part = if part_num == 1 { &day.part1 } else { &day.part2 };
+                let run = execute_part(part, &inputs[day_idx]);
+                finished_jobs.lock().unwrap()[job_idx] = Some(run);
+            });
+        }
+    });
+    let wall_time = wall_start.elapsed();
+
+    let finished_jobs = finished_jobs.into_inner().unwrap();
+    let mut cpu_time = Duration::ZERO;
+    for (day_idx, day) in days.iter().enumerate() {
+        print_day_header(day);
+        for part_num in [1, 2] {
+            let job_idx = jobs.iter().position(|&job| job == (day_idx, part_num)).unwrap();
+            let run = finished_jobs[job_idx].as_ref().expect("all jobs are finished");
+            cpu_time += run.duration;
+            let known = answers.part(day.name, &inputs[day_idx], part_num);
+            print_part_run(&format!("Part{part_num}"), run, known);
+        }
+    }
+
+    println!();
+    println!(
+        "Ran {num_parts} parts on {num_threads} threads: CPU time {cpu_time:?}, wall time {wall_time:?} (x{speedup:.2})",
+        num_parts = jobs.len(),
+        speedup = cpu_time.as_secs_f64() / wall_time.as_secs_f64().max(f64::EPSILON),
+    );
+    Ok(())
+}
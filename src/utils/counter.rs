@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Count occurrences of items, like Python's `collections.Counter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<T: Eq + Hash> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash> Default for Counter<T> {
+    fn default() -> Self {
+        Self { counts: HashMap::new() }
+    }
+}
+
+impl<T: Eq + Hash> Counter<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    pub fn add_n(&mut self, item: T, n: usize) {
+        if n == 0 {
+            return;
+        }
+        *self.counts.entry(item).or_insert(0) += n;
+    }
+
+    /// Count of the given item, 0 if never seen
+    pub fn count(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct items
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Total count of all items
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(item, &count)| (item, count))
+    }
+
+    /// All items with their count, most common first (ties are ordered by item)
+    pub fn most_common(&self) -> Vec<(&T, usize)>
+    where
+        T: Ord,
+    {
+        let mut items: Vec<_> = self.iter().collect();
+        items.sort_by(|(item_a, count_a), (item_b, count_b)| {
+            count_b.cmp(count_a).then_with(|| item_a.cmp(item_b))
+        });
+        items
+    }
+
+    /// Items present in both counters, with the minimum of both counts
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        let mut result = Self::new();
+        for (item, count) in self.iter() {
+            result.add_n(item.clone(), count.min(other.count(item)));
+        }
+        result
+    }
+
+    /// Items present in any counter, with the maximum of both counts
+    pub fn union(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        let mut result = self.clone();
+        for (item, count) in other.iter() {
+            let current = result.count(item);
+            if count > current {
+                result.add_n(item.clone(), count - current);
+            }
+        }
+        result
+    }
+
+    /// Sum of `weight(item) * count` for all items
+    pub fn weighted_sum(&self, weight: impl Fn(&T) -> usize) -> usize {
+        self.iter().map(|(item, count)| weight(item) * count).sum()
+    }
+
+    /// Same as `weighted_sum`, but `None` when a weight (`None` too) or the sum overflows
+    pub fn checked_weighted_sum(&self, weight: impl Fn(&T) -> Option<usize>) -> Option<usize> {
+        self.iter().try_fold(0usize, |total, (item, count)| {
+            total.checked_add(weight(item)?.checked_mul(count)?)
+        })
+    }
+}
+
+impl<T: Eq + Hash> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T: Eq + Hash> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_from_iter() {
+        let counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(counter.count(&'a'), 5);
+        assert_eq!(counter.count(&'b'), 2);
+        assert_eq!(counter.count(&'z'), 0);
+        assert_eq!(counter.len(), 5);
+        assert_eq!(counter.total(), 11);
+        assert!(!counter.is_empty());
+        assert!(Counter::<char>::new().is_empty());
+    }
+
+    #[test]
+    fn test_most_common() {
+        let counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(counter.most_common(), vec![(&'a', 5), (&'b', 2), (&'r', 2), (&'c', 1), (&'d', 1)]);
+    }
+
+    #[test]
+    fn test_intersection_and_union() {
+        let left: Counter<u32> = [1, 1, 1, 2, 3].into_iter().collect();
+        let right: Counter<u32> = [1, 2, 2, 4].into_iter().collect();
+
+        let intersection = left.intersection(&right);
+        assert_eq!(intersection, [1, 2].into_iter().collect());
+        assert_eq!(intersection.count(&3), 0);
+
+        let union = left.union(&right);
+        assert_eq!(union, [1, 1, 1, 2, 2, 3, 4].into_iter().collect());
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        let counter: Counter<usize> = [3, 3, 5].into_iter().collect();
+        assert_eq!(counter.weighted_sum(|&item| item), 11);
+        assert_eq!(counter.weighted_sum(|_| 1), counter.total());
+    }
+
+    #[test]
+    fn test_checked_weighted_sum() {
+        let counter: Counter<usize> = [3, 3, 5].into_iter().collect();
+        assert_eq!(counter.checked_weighted_sum(|&item| Some(item)), Some(11));
+        assert_eq!(counter.checked_weighted_sum(|&item| Some(item * (usize::MAX / 8))), None);
+        assert_eq!(counter.checked_weighted_sum(|&item| (item != 5).then_some(item)), None);
+    }
+}
@@ -0,0 +1,577 @@
+use std::fmt;
+
+use anyhow::{bail, Result as AnyResult};
+
+/// Type of grid coordinates, signed to go 1 step outside the grid in any direction
+pub type Coord = i32;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Pos {
+    pub x: Coord,
+    pub y: Coord,
+}
+impl fmt::Debug for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pos(x: {x}, y: {y})", x = self.x, y = self.y)
+    }
+}
+
+/// What all grids of chars can do, dense or sparse
+pub trait CharGrid {
+    /// The char at the position, `None` when outside of the grid
+    fn get_at(&self, pos: &Pos) -> Option<char>;
+
+    /// All chars of the grid with their position, line by line
+    fn iter_with_position(&self) -> impl Iterator<Item = (Pos, char)> + '_;
+
+    /// Chars around the position (in the grid), with their direction & position
+    fn neighbours(&self, pos: &Pos) -> impl Iterator<Item = (Direction, Pos, char)> + '_ {
+        let pos = *pos;
+        Direction::ALL.into_iter().filter_map(move |dir| {
+            let next_pos = dir.checked_next_pos(&pos)?;
+            self.get_at(&next_pos).map(|chr| (dir, next_pos, chr))
+        })
+    }
+}
+
+/// Grid of chars, as lines of the same width.
+///
+/// Displayed as its lines (each ending with a newline), like in puzzle inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    data: Vec<Vec<char>>,
+    width: usize,
+    height: usize,
+}
+impl Grid {
+    pub fn new(data: Vec<Vec<char>>) -> AnyResult<Self> {
+        let Some(first_line) = data.first() else {
+            bail!("Empty grid");
+        };
+        let width = first_line.len();
+        if let Some(bad_idx) = data.iter().position(|line| line.len() != width) {
+            bail!(
+                "Line {line_num} has {len} chars, but grid width is {width} (from first line)",
+                line_num = bad_idx + 1,
+                len = data[bad_idx].len(),
+            );
+        }
+        let height = data.len();
+        // NOTE: Checked once here, so positions in the grid can't overflow, even 1 step outside
+        check_coord_limit("wide", "columns", width)?;
+        check_coord_limit("tall", "lines", height)?;
+        Ok(Self { data, width, height })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// View of the full grid, to transform it without copying
+    pub fn view(&self) -> GridView<'_> {
+        GridView {
+            grid: self,
+            transposed: false,
+            flipped_x: false,
+            flipped_y: false,
+            origin_x: 0,
+            origin_y: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+impl CharGrid for Grid {
+    fn get_at(&self, pos: &Pos) -> Option<char> {
+        if pos.x < 0 || pos.y < 0 || self.width <= (pos.x as usize) || self.height <= (pos.y as usize) {
+            return None;
+        }
+        let line = self.data.get(pos.y as usize);
+        let maybe_char = line.and_then(|line| line.get(pos.x as usize));
+        maybe_char.copied()
+    }
+
+    fn iter_with_position(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.data.iter()
+            .enumerate()
+            .flat_map(|(y_idx, line)| {
+                line.iter()
+                    .copied()
+                    .enumerate()
+                    .map(move |(x_idx, chr)| {
+                        // NOTE: Can't wrap, the grid size is checked in `Grid::new`
+                        let pos = Pos {
+                            x: x_idx as Coord,
+                            y: y_idx as Coord,
+                        };
+                        (pos, chr)
+                    })
+            })
+    }
+}
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+/// Ensure all indices of a grid dimension, and 1 past them, are valid coordinates
+pub fn check_coord_limit(adjective: &str, unit: &str, size: usize) -> AnyResult<()> {
+    if Coord::try_from(size).is_err() {
+        bail!("Grid is too {adjective}: {size} {unit}, at most {max} are supported", max = Coord::MAX);
+    }
+    Ok(())
+}
+
+/// Transformed (rotated, flipped, cropped..) view of a grid, without copying it.
+///
+/// Transforms are combined into a single mapping from view positions to grid positions: the grid
+/// is transposed (or not), then flipped on each axis (or not), then cropped.
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a> {
+    grid: &'a Grid,
+    transposed: bool,
+    flipped_x: bool,
+    flipped_y: bool,
+    /// Top-left corner of the view, in the transposed & flipped grid
+    origin_x: usize,
+    origin_y: usize,
+    width: usize,
+    height: usize,
+}
+impl GridView<'_> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of the transposed grid, before cropping
+    fn full_size(&self) -> (usize, usize) {
+        if self.transposed {
+            (self.grid.height, self.grid.width)
+        } else {
+            (self.grid.width, self.grid.height)
+        }
+    }
+
+    /// Lines become columns: the char at (x, y) goes to (y, x)
+    pub fn transpose(self) -> Self {
+        Self {
+            transposed: !self.transposed,
+            flipped_x: self.flipped_y,
+            flipped_y: self.flipped_x,
+            origin_x: self.origin_y,
+            origin_y: self.origin_x,
+            width: self.height,
+            height: self.width,
+            ..self
+        }
+    }
+
+    /// Mirror left to right
+    pub fn flip_horizontal(self) -> Self {
+        let (full_width, _) = self.full_size();
+        Self {
+            flipped_x: !self.flipped_x,
+            origin_x: full_width - (self.origin_x + self.width),
+            ..self
+        }
+    }
+
+    /// Mirror top to bottom
+    pub fn flip_vertical(self) -> Self {
+        let (_, full_height) = self.full_size();
+        Self {
+            flipped_y: !self.flipped_y,
+            origin_y: full_height - (self.origin_y + self.height),
+            ..self
+        }
+    }
+
+    /// Rotate by 90°, clockwise
+    pub fn rotate_cw(self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    /// Rotate by 90°, counter-clockwise
+    pub fn rotate_ccw(self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    /// Rotate by 180°
+    pub fn rotate_half(self) -> Self {
+        self.flip_horizontal().flip_vertical()
+    }
+
+    /// Sub-grid of `width` x `height` chars, starting at `top_left`
+    pub fn crop(self, top_left: Pos, width: usize, height: usize) -> AnyResult<Self> {
+        if width == 0 || height == 0 {
+            bail!("Empty crop of {width}x{height}");
+        }
+        let is_inside = top_left.x >= 0
+            && top_left.y >= 0
+            && (top_left.x as usize).checked_add(width).is_some_and(|end_x| end_x <= self.width)
+            && (top_left.y as usize).checked_add(height).is_some_and(|end_y| end_y <= self.height);
+        if !is_inside {
+            bail!(
+                "Crop of {width}x{height} at {top_left:?} is outside of the {view_width}x{view_height} grid",
+                view_width = self.width,
+                view_height = self.height,
+            );
+        }
+        Ok(Self {
+            origin_x: self.origin_x + top_left.x as usize,
+            origin_y: self.origin_y + top_left.y as usize,
+            width,
+            height,
+            ..self
+        })
+    }
+
+    /// Copy of the view as a grid of its own
+    pub fn to_grid(self) -> Grid {
+        let data = (0..self.height)
+            .map(|y_idx| {
+                (0..self.width)
+                    // NOTE: Always in the view, and it's never bigger than the grid
+                    .map(|x_idx| self.get_at(&Pos { x: x_idx as Coord, y: y_idx as Coord }).unwrap())
+                    .collect()
+            })
+            .collect();
+        Grid { data, width: self.width, height: self.height }
+    }
+
+    /// Grid made of the view repeated `times_x` times horizontally & `times_y` times vertically
+    pub fn tile(&self, times_x: usize, times_y: usize) -> AnyResult<Grid> {
+        if times_x == 0 || times_y == 0 {
+            bail!("Can't tile a grid {times_x}x{times_y} times");
+        }
+        // NOTE: Checked before building it, as it could take all the memory
+        let width = self.width.saturating_mul(times_x);
+        let height = self.height.saturating_mul(times_y);
+        check_coord_limit("wide", "columns", width)?;
+        check_coord_limit("tall", "lines", height)?;
+        let tile = self.to_grid();
+        let data = (0..times_y)
+            .flat_map(|_| tile.data.iter())
+            .map(|line| line.repeat(times_x))
+            .collect();
+        Grid::new(data)
+    }
+}
+impl CharGrid for GridView<'_> {
+    fn get_at(&self, pos: &Pos) -> Option<char> {
+        if pos.x < 0 || pos.y < 0 || self.width <= (pos.x as usize) || self.height <= (pos.y as usize) {
+            return None;
+        }
+        let (full_width, full_height) = self.full_size();
+        let x = self.origin_x + pos.x as usize;
+        let y = self.origin_y + pos.y as usize;
+        let x = if self.flipped_x { full_width - 1 - x } else { x };
+        let y = if self.flipped_y { full_height - 1 - y } else { y };
+        let (x, y) = if self.transposed { (y, x) } else { (x, y) };
+        Some(self.grid.data[y][x])
+    }
+
+    fn iter_with_position(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        (0..self.height).flat_map(move |y_idx| {
+            (0..self.width).map(move |x_idx| {
+                // NOTE: Can't wrap, the view is never bigger than the grid
+                let pos = Pos { x: x_idx as Coord, y: y_idx as Coord };
+                // NOTE: Always in the view
+                (pos, self.get_at(&pos).unwrap())
+            })
+        })
+    }
+}
+impl fmt::Display for GridView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y_idx in 0..self.height {
+            for x_idx in 0..self.width {
+                // NOTE: Always in the view, and it's never bigger than the grid
+                let chr = self.get_at(&Pos { x: x_idx as Coord, y: y_idx as Coord }).unwrap();
+                write!(f, "{chr}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::TopLeft,
+        Direction::Top,
+        Direction::TopRight,
+        Direction::Left,
+        Direction::Right,
+        Direction::BottomLeft,
+        Direction::Bottom,
+        Direction::BottomRight,
+    ];
+
+    /// Move of one step in this direction, as `(dx, dy)`
+    pub fn delta(&self) -> (Coord, Coord) {
+        let next = self.next_pos(&Pos { x: 0, y: 0 });
+        (next.x, next.y)
+    }
+
+    pub fn next_pos(&self, pos: &Pos) -> Pos {
+        match self {
+            Direction::TopLeft => {
+                Pos { x: pos.x - 1, y: pos.y - 1 }
+            },
+            Direction::Top => {
+                Pos { x: pos.x,     y: pos.y - 1 }
+            },
+            Direction::TopRight => {
+                Pos { x: pos.x + 1, y: pos.y - 1 }
+            },
+            Direction::Left => {
+                Pos { x: pos.x - 1, y: pos.y }
+            },
+            Direction::Right => {
+                Pos { x: pos.x + 1, y: pos.y }
+            },
+            Direction::BottomLeft => {
+                Pos { x: pos.x - 1, y: pos.y + 1 }
+            },
+            Direction::Bottom => {
+                Pos { x: pos.x,     y: pos.y + 1 }
+            },
+            Direction::BottomRight => {
+                Pos { x: pos.x + 1, y: pos.y + 1 }
+            },
+        }
+    }
+
+    /// Same as `next_pos`, but `None` when the step goes past the coordinate limits
+    pub fn checked_next_pos(&self, pos: &Pos) -> Option<Pos> {
+        let (dx, dy) = self.delta();
+        Some(Pos { x: pos.x.checked_add(dx)?, y: pos.y.checked_add(dy)? })
+    }
+
+    pub fn reverse_dir(&self) -> Self {
+        match self {
+            Direction::TopLeft => Direction::BottomRight,
+            Direction::Top => Direction::Bottom,
+            Direction::TopRight => Direction::BottomLeft,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::BottomLeft => Direction::TopRight,
+            Direction::Bottom => Direction::Top,
+            Direction::BottomRight => Direction::TopLeft,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsing::parse_char_grid;
+
+    #[test]
+    fn test_grid_get_in_bound() {
+        let grid = Grid::new(vec![
+            // x: 0    1    2       / y:
+            vec!['a', 'b', 'c'], // line 0
+            vec!['d', 'e', 'f'], // line 1
+        ]).unwrap();
+        assert_eq!(grid.get_at(&Pos { x: 0, y: 0 }), Some('a'));
+        assert_eq!(grid.get_at(&Pos { x: 1, y: 1 }), Some('e'));
+    }
+
+    #[test]
+    fn test_grid_get_out_of_bound() {
+        let grid = Grid::new(vec![
+            // x: 0    1    2       / y:
+            vec!['a', 'b', 'c'], // line 0
+            vec!['d', 'e', 'f'], // line 1
+        ]).unwrap();
+        assert_eq!(grid.get_at(&Pos { x: 0, y: -1 }), None);
+        assert_eq!(grid.get_at(&Pos { x: -1, y: 0 }), None);
+        assert_eq!(grid.get_at(&Pos { x: 0, y: 3 }), None);
+        assert_eq!(grid.get_at(&Pos { x: 2, y: 3 }), None);
+    }
+
+    #[test]
+    fn test_grid_size_limit() {
+        assert!(check_coord_limit("wide", "columns", Coord::MAX as usize).is_ok());
+        assert_eq!(
+            check_coord_limit("wide", "columns", Coord::MAX as usize + 1).unwrap_err().to_string(),
+            "Grid is too wide: 2147483648 columns, at most 2147483647 are supported",
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(parse_char_grid("abc\ndef\n").unwrap().to_string(), "abc\ndef\n");
+        assert_eq!(parse_char_grid("abc\ndef\n").unwrap().view().to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = parse_char_grid("abc\ndef\n").unwrap();
+        let view = grid.view();
+        assert_eq!(view.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(view.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(view.rotate_ccw().to_string(), "cf\nbe\nad\n");
+        assert_eq!(view.rotate_half().to_string(), "fed\ncba\n");
+        assert_eq!(view.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(view.flip_vertical().to_string(), "def\nabc\n");
+        assert_eq!(view.rotate_cw().rotate_cw().rotate_cw().rotate_cw().to_string(), "abc\ndef\n");
+        assert_eq!(view.rotate_cw().rotate_ccw().to_string(), "abc\ndef\n");
+        assert_eq!((view.rotate_cw().width(), view.rotate_cw().height()), (2, 3));
+        assert_eq!(view.rotate_cw().get_at(&Pos { x: 0, y: 2 }), Some('f'));
+        assert_eq!(view.rotate_cw().get_at(&Pos { x: 2, y: 0 }), None);
+    }
+
+    #[test]
+    fn test_crop() {
+        let grid = parse_char_grid("abc\ndef\nghi\n").unwrap();
+        let view = grid.view();
+        let cropped = view.crop(Pos { x: 1, y: 0 }, 2, 2).unwrap();
+        assert_eq!(cropped.to_string(), "bc\nef\n");
+        // Transforms of a cropped view only see the cropped part
+        assert_eq!(cropped.rotate_cw().to_string(), "eb\nfc\n");
+        assert_eq!(cropped.flip_vertical().to_string(), "ef\nbc\n");
+        // Crop of a transformed view
+        assert_eq!(view.rotate_cw().crop(Pos { x: 1, y: 1 }, 2, 1).unwrap().to_string(), "eb\n");
+        assert_eq!(cropped.crop(Pos { x: 1, y: 1 }, 1, 1).unwrap().to_string(), "f\n");
+
+        assert_eq!(
+            view.crop(Pos { x: 2, y: 1 }, 2, 1).unwrap_err().to_string(),
+            "Crop of 2x1 at Pos(x: 2, y: 1) is outside of the 3x3 grid",
+        );
+        assert!(view.crop(Pos { x: -1, y: 0 }, 1, 1).is_err());
+        assert_eq!(view.crop(Pos { x: 0, y: 0 }, 0, 1).unwrap_err().to_string(), "Empty crop of 0x1");
+    }
+
+    #[test]
+    fn test_tile() {
+        let grid = parse_char_grid("ab\ncd\n").unwrap();
+        assert_eq!(grid.view().tile(2, 3).unwrap().to_string(), "abab\ncdcd\nabab\ncdcd\nabab\ncdcd\n");
+        assert_eq!(grid.view().flip_horizontal().tile(2, 1).unwrap().to_string(), "baba\ndcdc\n");
+        assert_eq!(grid.view().tile(0, 1).unwrap_err().to_string(), "Can't tile a grid 0x1 times");
+        assert_eq!(
+            grid.view().tile(usize::MAX, 1).unwrap_err().to_string(),
+            format!("Grid is too wide: {} columns, at most 2147483647 are supported", usize::MAX),
+        );
+    }
+
+    #[test]
+    fn test_to_grid() {
+        let grid = parse_char_grid("abc\ndef\n").unwrap();
+        let rotated = grid.view().rotate_cw().to_grid();
+        assert_eq!(rotated, Grid::new(vec![vec!['d', 'a'], vec!['e', 'b'], vec!['f', 'c']]).unwrap());
+        assert_eq!((rotated.width(), rotated.height()), (2, 3));
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::strategies;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Transform {
+        Transpose,
+        FlipHorizontal,
+        FlipVertical,
+        RotateCw,
+        RotateCcw,
+        /// Crop from the given fractions of the current size
+        Crop(f64, f64, f64, f64),
+    }
+
+    fn transform() -> impl Strategy<Value = Transform> {
+        prop_oneof![
+            Just(Transform::Transpose),
+            Just(Transform::FlipHorizontal),
+            Just(Transform::FlipVertical),
+            Just(Transform::RotateCw),
+            Just(Transform::RotateCcw),
+            (0.0..1.0, 0.0..1.0, 0.0..1.0, 0.0..1.0).prop_map(|(x, y, w, h)| Transform::Crop(x, y, w, h)),
+        ]
+    }
+
+    // Crop position & size in a dimension of `size`, from fractions of it
+    fn crop_range(size: usize, start: f64, len: f64) -> (usize, usize) {
+        let start_idx = (start * size as f64) as usize;
+        let len = 1 + (len * (size - start_idx) as f64) as usize;
+        (start_idx, len.min(size - start_idx))
+    }
+
+    // Apply the transform by copying chars around
+    fn apply_reference(rows: &[Vec<char>], transform: Transform) -> Vec<Vec<char>> {
+        let height = rows.len();
+        let width = rows[0].len();
+        let transposed: Vec<Vec<char>> = (0..width).map(|x| (0..height).map(|y| rows[y][x]).collect()).collect();
+        let flip_lines = |rows: Vec<Vec<char>>| rows.into_iter().map(|line| line.into_iter().rev().collect()).collect();
+        match transform {
+            Transform::Transpose => transposed,
+            Transform::FlipHorizontal => flip_lines(rows.to_vec()),
+            Transform::FlipVertical => rows.iter().rev().cloned().collect(),
+            Transform::RotateCw => flip_lines(transposed),
+            Transform::RotateCcw => transposed.into_iter().rev().collect(),
+            Transform::Crop(x, y, w, h) => {
+                let (x, w) = crop_range(width, x, w);
+                let (y, h) = crop_range(height, y, h);
+                rows[y..y + h].iter().map(|line| line[x..x + w].to_vec()).collect()
+            }
+        }
+    }
+
+    fn apply<'a>(view: GridView<'a>, transform: Transform) -> GridView<'a> {
+        match transform {
+            Transform::Transpose => view.transpose(),
+            Transform::FlipHorizontal => view.flip_horizontal(),
+            Transform::FlipVertical => view.flip_vertical(),
+            Transform::RotateCw => view.rotate_cw(),
+            Transform::RotateCcw => view.rotate_ccw(),
+            Transform::Crop(x, y, w, h) => {
+                let (x, w) = crop_range(view.width(), x, w);
+                let (y, h) = crop_range(view.height(), y, h);
+                view.crop(Pos { x: x as Coord, y: y as Coord }, w, h).unwrap()
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn transforms_match_reference(
+            rows in strategies::letter_grid(&['a', 'b', 'c', 'd', 'e'], 6, 6),
+            transforms in prop::collection::vec(transform(), 0..8),
+        ) {
+            let grid = Grid::new(rows.clone()).unwrap();
+            let mut view = grid.view();
+            let mut expected = rows;
+            for transform in transforms {
+                view = apply(view, transform);
+                expected = apply_reference(&expected, transform);
+            }
+            prop_assert_eq!(view.to_string(), strategies::grid_to_input(&expected));
+            prop_assert_eq!(view.to_grid(), Grid::new(expected).unwrap());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#[cfg_attr(not(test), allow(dead_code))]
+pub mod counter;
+#[cfg_attr(not(test), allow(dead_code))]
+pub mod grid;
+#[cfg_attr(not(test), allow(dead_code))]
+pub mod parsing;
+pub mod rng;
+#[cfg_attr(not(test), allow(dead_code))]
+pub mod sparse_grid;
+#[cfg_attr(not(test), allow(dead_code))]
+pub mod table;
+#[cfg(test)]
+pub mod strategies;
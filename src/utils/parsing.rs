@@ -0,0 +1,400 @@
+//! Parsing helpers for the usual shapes of puzzle inputs.
+//!
+//! Parsers here are nom parsers, run on the whole input (or each line / block of it) with the
+//! `parse_*` functions, which require the input to be fully consumed and give errors telling
+//! where in the input it failed.
+//!
+//! Line endings can be `\n` or `\r\n` and trailing whitespace at the end of lines (or of the input)
+//! is ignored, but anything else left unparsed is an error.
+//! Except in char grids, where spaces can be cells: only their blank lines at the end are ignored.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use nom::{
+    bytes::complete::take_till1,
+    character::complete::{char, digit1, space0, space1},
+    combinator::{map_res, opt, recognize},
+    error::ErrorKind,
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, pair, separated_pair, tuple},
+    IResult, Parser,
+};
+
+use crate::utils::grid::Grid;
+
+type NomError<'a> = nom::error::Error<&'a str>;
+
+/// Convert a nom error to an error telling where in the input it failed (line & column).
+///
+/// NOTE: nom errors borrow the input, so they can't be returned as is in an `anyhow::Error`.
+pub fn nom_error(input: &str, err: nom::Err<NomError<'_>>) -> anyhow::Error {
+    match err {
+        nom::Err::Incomplete(_) => anyhow!("Incomplete input"),
+        nom::Err::Error(err) | nom::Err::Failure(err) => {
+            let (line, column) = position_of(input, err.input);
+            anyhow!(
+                "Failed to parse input at line {line}, column {column}: {expected} near: {snippet:?}",
+                expected = describe_error(err.code),
+                snippet = snippet(err.input),
+            )
+        }
+    }
+}
+
+/// What was expected when the parser failed with this error kind
+fn describe_error(kind: ErrorKind) -> String {
+    match kind {
+        ErrorKind::Digit => "expected a number".to_string(),
+        ErrorKind::MapRes => "invalid number (out of range for its type)".to_string(),
+        ErrorKind::Char => "expected a separator char".to_string(),
+        ErrorKind::Tag => "expected a fixed text".to_string(),
+        ErrorKind::Space => "expected spaces".to_string(),
+        ErrorKind::TakeTill1 => "expected a non-empty key".to_string(),
+        kind => format!("unexpected input ({kind:?})"),
+    }
+}
+
+/// Line & column (both starting at 1) where `rest` starts in `input`,
+/// `rest` must be a sub-slice of `input` (as a nom remainder is, even when parsing a line of it).
+pub fn position_of(input: &str, rest: &str) -> (usize, usize) {
+    let offset = (rest.as_ptr() as usize).checked_sub(input.as_ptr() as usize)
+        .filter(|&offset| offset <= input.len())
+        .expect("rest must be a sub-slice of input");
+    let consumed = &input[..offset];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed.chars().rev().take_while(|&chr| chr != '\n').count() + 1;
+    (line, column)
+}
+
+/// Run the parser on a part of the input (a sub-slice of it), which must be fully consumed.
+fn parse_part<'a, T>(
+    input: &'a str,
+    part: &'a str,
+    parser: &mut impl Parser<&'a str, T, NomError<'a>>,
+) -> AnyResult<T> {
+    let (rest, value) = parser.parse(part).map_err(|err| nom_error(input, err))?;
+    if !rest.is_empty() {
+        let (line_num, column) = position_of(input, rest);
+        bail!("Unparsed leftover at line {line_num}, column {column}: {:?}", snippet(rest));
+    }
+    Ok(value)
+}
+
+/// Parse the whole input, the parser must consume all of it (except trailing whitespace).
+pub fn parse_all<'a, T>(input: &'a str, mut parser: impl Parser<&'a str, T, NomError<'a>>) -> AnyResult<T> {
+    parse_part(input, input.trim_end(), &mut parser)
+}
+
+/// Parse each line of the input, the parser must consume the whole line (except trailing whitespace).
+/// Blank lines at the end of the input are ignored, not the ones before.
+///
+/// Errors (including the unparsed leftover of a line) tell where they are in the whole input.
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut line_parser: impl Parser<&'a str, T, NomError<'a>>,
+) -> AnyResult<Vec<T>> {
+    // NOTE: `lines` already strips the `\r` of `\r\n` line endings
+    input.trim_end().lines()
+        .map(|line| parse_part(input, line.trim_end(), &mut line_parser))
+        .collect()
+}
+
+/// Blocks of lines of the input, separated by one or more blank lines (without their last line ending).
+pub fn blocks(input: &str) -> Vec<&str> {
+    let mut blocks = vec![];
+    let mut block_start = None;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let is_blank = line.trim().is_empty();
+        match (block_start, is_blank) {
+            (None, false) => block_start = Some(offset),
+            (Some(start), true) => {
+                blocks.push(input[start..offset].trim_end_matches(['\r', '\n']));
+                block_start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some(start) = block_start {
+        blocks.push(input[start..].trim_end_matches(['\r', '\n']));
+    }
+    blocks
+}
+
+/// Parse each block of lines of the input (see `blocks`), the parser must consume the whole block.
+pub fn parse_blocks<'a, T>(
+    input: &'a str,
+    mut block_parser: impl Parser<&'a str, T, NomError<'a>>,
+) -> AnyResult<Vec<T>> {
+    blocks(input).into_iter()
+        .map(|block| parse_part(input, block, &mut block_parser))
+        .collect()
+}
+
+/// Parse the input as a grid of chars, all lines must have the same width.
+///
+/// NOTE: Spaces are kept, even at the end of lines, they can be cells of the grid.
+pub fn parse_char_grid(input: &str) -> AnyResult<Grid> {
+    let content = input.trim_end_matches(['\r', '\n']);
+    Grid::new(content.lines().map(|line| line.chars().collect()).collect())
+}
+
+/// Input with `\n` line endings and ending with a single newline (unless empty), like inputs saved
+/// on Windows or by some editors look once fixed.
+///
+/// NOTE: Trailing spaces are kept, they can be cells of a grid (`parse_lines` & `parse_all` ignore
+///   them anyway).
+///
+/// Borrowed as is when there is nothing to fix, like for downloaded inputs.
+pub fn normalize_input(input: &str) -> Cow<'_, str> {
+    let content = input.trim_end_matches(['\r', '\n']);
+    let is_normalized = input.is_empty() || (
+        !content.is_empty()
+        && input.len() == content.len() + 1 && input.ends_with('\n')
+        && !content.contains("\r\n")
+    );
+    if is_normalized {
+        return Cow::Borrowed(input);
+    }
+    if content.is_empty() {
+        return Cow::Owned(String::new());
+    }
+    let mut normalized = content.replace("\r\n", "\n");
+    normalized.push('\n');
+    Cow::Owned(normalized)
+}
+
+// --------------------------------------------------------
+
+/// Integer of any type, with an optional sign (rejected by unsigned types).
+///
+/// NOTE: An integer too big for its type is an error, not a number cut short.
+pub fn integer<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(char('-')), digit1)), str::parse)(input)
+}
+
+/// Integers separated by spaces (or tabs), with optional spaces around the list.
+pub fn integer_list<T: FromStr>(input: &str) -> IResult<&str, Vec<T>> {
+    delimited(space0, separated_list0(space1, integer), space0)(input)
+}
+
+/// Same as `integer_list`, but with at least one integer.
+pub fn integer_list1<T: FromStr>(input: &str) -> IResult<&str, Vec<T>> {
+    delimited(space0, separated_list1(space1, integer), space0)(input)
+}
+
+/// Pair of integers separated by a comma, like `12,34` (spaces around the comma are allowed).
+pub fn comma_pair<T: FromStr>(input: &str) -> IResult<&str, (T, T)> {
+    separated_pair(integer, tuple((space0, char(','), space0)), integer)(input)
+}
+
+/// Line like `key: value`, the key being everything before the `:` (without trailing spaces).
+pub fn key_value<'a, V>(
+    mut value_parser: impl Parser<&'a str, V, NomError<'a>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (&'a str, V)> {
+    move |input| {
+        let (rest, key) = take_till1(|chr| chr == ':' || chr == '\n')(input)?;
+        let (rest, _) = tuple((char(':'), space0))(rest)?;
+        let (rest, value) = value_parser.parse(rest)?;
+        Ok((rest, (key.trim_end(), value)))
+    }
+}
+
+/// First few chars of some text, to show it in errors
+pub fn snippet(text: &str) -> String {
+    const MAX_CHARS: usize = 20;
+    let mut snippet: String = text.chars().take(MAX_CHARS).collect();
+    if text.chars().nth(MAX_CHARS).is_some() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::character::complete::u32 as parse_u32;
+
+    use super::*;
+
+    #[test]
+    fn test_position_of() {
+        let input = "abc\ndef\nghi";
+        assert_eq!(position_of(input, input), (1, 1));
+        assert_eq!(position_of(input, &input[2..]), (1, 3));
+        assert_eq!(position_of(input, &input[4..]), (2, 1));
+        assert_eq!(position_of(input, &input[9..]), (3, 2));
+        assert_eq!(position_of(input, &input[input.len()..]), (3, 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "rest must be a sub-slice of input")]
+    fn test_position_of_other_text() {
+        let text = String::from("abc\ndef");
+        position_of(&text[4..], &text[..2]);
+    }
+
+    #[test]
+    fn test_nom_error() {
+        let input = "123\nabc";
+        let err = parse_u32::<&str, nom::error::Error<&str>>(&input[4..]).unwrap_err();
+        assert_eq!(
+            nom_error(input, err).to_string(),
+            "Failed to parse input at line 2, column 1: expected a number near: \"abc\"",
+        );
+    }
+
+    #[test]
+    fn test_position_of_line() {
+        let input = "abc\ndef\nghi";
+        let line = input.lines().nth(1).unwrap();
+        assert_eq!(position_of(input, &line[1..]), (2, 2));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(parse_lines("1\n22\n333\n", parse_u32).unwrap(), vec![1, 22, 333]);
+        assert_eq!(parse_lines("", parse_u32).unwrap(), vec![]);
+        assert_eq!(
+            parse_lines("1\n22x\n333\n", parse_u32).unwrap_err().to_string(),
+            "Unparsed leftover at line 2, column 3: \"x\"",
+        );
+        assert_eq!(
+            parse_lines("1\n\n333\n", parse_u32).unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 1: expected a number near: \"\"",
+        );
+    }
+
+    #[test]
+    fn test_parse_lines_windows_input() {
+        assert_eq!(parse_lines("1\r\n22 \r\n333\t\r\n", parse_u32).unwrap(), vec![1, 22, 333]);
+        assert_eq!(parse_lines("1\r\n22\r\n", parse_u32).unwrap(), vec![1, 22]);
+        // A lone `\r` isn't a line ending
+        assert_eq!(
+            parse_lines("1\r\n22\r3\r\n", parse_u32).unwrap_err().to_string(),
+            "Unparsed leftover at line 2, column 3: \"\\r3\"",
+        );
+    }
+
+    #[test]
+    fn test_parse_all() {
+        assert_eq!(parse_all("1 2 3", integer_list::<u32>).unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_all("1 2 3 \r\n\r\n", integer_list::<u32>).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            parse_all("1 2\n3", integer_list::<u32>).unwrap_err().to_string(),
+            "Unparsed leftover at line 1, column 4: \"\\n3\"",
+        );
+    }
+
+    #[test]
+    fn test_integer() {
+        assert_eq!(integer::<i32>("-12x"), Ok(("x", -12)));
+        assert_eq!(integer::<u8>("255"), Ok(("", 255)));
+        let input = "1\n256";
+        assert_eq!(
+            parse_lines(input, integer::<u8>).unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 1: invalid number (out of range for its type) near: \"256\"",
+        );
+        assert!(parse_all("-1", integer::<u32>).is_err());
+        assert!(parse_all("+1", integer::<u32>).is_err());
+    }
+
+    #[test]
+    fn test_integer_list() {
+        assert_eq!(parse_all("1 2\t 3", integer_list::<u32>).unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_all("  -1   2  ", integer_list::<i32>).unwrap(), vec![-1, 2]);
+        assert_eq!(parse_all("", integer_list::<i32>).unwrap(), vec![]);
+        assert_eq!(
+            parse_all("1 2 x 3", integer_list::<i32>).unwrap_err().to_string(),
+            "Unparsed leftover at line 1, column 5: \"x 3\"",
+        );
+    }
+
+    #[test]
+    fn test_integer_list1() {
+        assert_eq!(parse_all(" 1 2", integer_list1::<u32>).unwrap(), vec![1, 2]);
+        assert_eq!(
+            parse_lines("1 2\n  \n3\n", integer_list1::<u32>).unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 1: expected a number near: \"\"",
+        );
+    }
+
+    #[test]
+    fn test_comma_pair() {
+        assert_eq!(parse_lines("1,2\n30 , -4\n", comma_pair::<i32>).unwrap(), vec![(1, 2), (30, -4)]);
+        assert_eq!(
+            parse_lines("1,2\n3;4\n", comma_pair::<i32>).unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 2: expected a separator char near: \";4\"",
+        );
+    }
+
+    #[test]
+    fn test_key_value() {
+        let input = "Button A: 94 34\nPrize:1 2\n";
+        assert_eq!(
+            parse_lines(input, key_value(integer_list::<u32>)).unwrap(),
+            vec![("Button A", vec![94, 34]), ("Prize", vec![1, 2])],
+        );
+        assert_eq!(
+            parse_lines("a: 1\n: 2\n", key_value(integer::<u32>)).unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 1: expected a non-empty key near: \": 2\"",
+        );
+        assert!(parse_lines("a 1\n", key_value(integer::<u32>)).is_err());
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(blocks("a\nb\n\nc\n\n\n\nd\ne"), vec!["a\nb", "c", "d\ne"]);
+        assert_eq!(blocks("\n\na\n  \nb\n\n"), vec!["a", "b"]);
+        assert_eq!(blocks(""), Vec::<&str>::new());
+        assert_eq!(blocks("a\r\nb\r\n\r\nc\r\n"), vec!["a\r\nb", "c"]);
+
+        let input = "1 2\n3\n\n4\n\n5 x\n";
+        let parse_block = |input| parse_blocks(input, separated_list0(char('\n'), integer_list::<u32>));
+        assert_eq!(parse_block("1 2\n3\n\n4\n").unwrap(), vec![vec![vec![1, 2], vec![3]], vec![vec![4]]]);
+        // Errors tell where they are in the whole input
+        assert_eq!(
+            parse_block(input).unwrap_err().to_string(),
+            "Unparsed leftover at line 6, column 3: \"x\"",
+        );
+    }
+
+    #[test]
+    fn test_parse_char_grid() {
+        let grid = parse_char_grid("ab\ncd\n").unwrap();
+        assert_eq!(grid.to_string(), "ab\ncd\n");
+        assert_eq!(
+            parse_char_grid("ab\nc\n").unwrap_err().to_string(),
+            "Line 2 has 1 chars, but grid width is 2 (from first line)",
+        );
+        assert_eq!(parse_char_grid("ab\r\ncd\r\n\r\n").unwrap(), grid);
+        // Trailing spaces are cells
+        assert_eq!(parse_char_grid("a \n b\n").unwrap().to_string(), "a \n b\n");
+        assert!(parse_char_grid("ab \ncd\n").is_err());
+    }
+
+    #[test]
+    fn test_normalize_input() {
+        assert!(matches!(normalize_input("1 2\n3\n"), Cow::Borrowed("1 2\n3\n")));
+        assert!(matches!(normalize_input(""), Cow::Borrowed("")));
+        assert_eq!(normalize_input("1 2\r\n3\r\n"), "1 2\n3\n");
+        assert_eq!(normalize_input("1 2\n3"), "1 2\n3\n");
+        assert_eq!(normalize_input("1 2\n3\n\n\r\n"), "1 2\n3\n");
+        // Trailing spaces are kept, they can be cells of a grid
+        assert!(matches!(normalize_input("a \n b\n"), Cow::Borrowed("a \n b\n")));
+        assert_eq!(normalize_input("a \r\n b\r\n \r\n"), "a \n b\n \n");
+        // Blank lines inside the input are kept (they separate blocks)
+        assert_eq!(normalize_input("1\r\n\r\n2\r\n"), "1\n\n2\n");
+        assert_eq!(normalize_input("\r\n\n"), "");
+        // But the parsers of numbers still ignore trailing spaces
+        assert_eq!(parse_lines(&normalize_input("1 2  \r\n3\t\r\n"), integer_list1::<u32>).unwrap(), [vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("short"), "short");
+        assert_eq!(snippet("this is a text longer than 20 chars"), "this is a text longe…");
+    }
+}
@@ -0,0 +1,69 @@
+use std::ops::Range;
+
+/// Small deterministic pseudo-random generator (SplitMix64).
+///
+/// NOTE: Not for anything serious, but the same seed always gives the same values, on all
+///   platforms and versions, which is what we want to generate reproducible inputs.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in the given (non-empty) range
+    pub fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot generate a number in an empty range");
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    /// Random index in `0..len`
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        self.gen_range(0..len as u64) as usize
+    }
+
+    /// `true` with the given probability
+    pub fn gen_bool(&mut self, probability: f64) -> bool {
+        (self.next_u64() as f64 / u64::MAX as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.gen_index(items.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let values = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..10).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(values(42), values(42));
+        assert_ne!(values(42), values(43));
+    }
+
+    #[test]
+    fn test_gen_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            let value = rng.gen_range(10..13);
+            assert!((10..13).contains(&value));
+        }
+        assert_eq!(rng.gen_range(5..6), 5);
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{bail, Result as AnyResult};
+
+use super::grid::{check_coord_limit, CharGrid, Coord, Direction, Grid, Pos};
+
+/// How positions outside of the set chars are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Topology {
+    /// Unbounded plane, where everything not set is empty
+    Infinite,
+    /// Plane of a fixed size, wrapping around at its edges (leaving by the right comes back by the
+    /// left, etc..)
+    Toroidal { width: Coord, height: Coord },
+}
+
+/// Grid of chars storing only the non-empty ones, for unbounded or mostly empty planes.
+///
+/// Every position has a char (the empty one when nothing is set there), so `get_at` is never
+/// `None`. Iterating or displaying the grid goes through the bounding box of the set chars (or the
+/// whole plane when toroidal), line by line, like a dense `Grid`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid {
+    cells: HashMap<Pos, char>,
+    empty: char,
+    topology: Topology,
+    /// Top-left & bottom-right corners (inclusive) of the set chars, `None` when there are none
+    bounding_box: Option<(Pos, Pos)>,
+}
+
+impl SparseGrid {
+    /// Unbounded grid, where `empty` is the char of all positions not set
+    pub fn new(empty: char) -> Self {
+        Self { cells: HashMap::new(), empty, topology: Topology::Infinite, bounding_box: None }
+    }
+
+    /// Grid of `width` x `height`, where positions wrap around the edges
+    pub fn toroidal(width: usize, height: usize, empty: char) -> AnyResult<Self> {
+        if width == 0 || height == 0 {
+            bail!("Empty toroidal grid of {width}x{height}");
+        }
+        check_coord_limit("wide", "columns", width)?;
+        check_coord_limit("tall", "lines", height)?;
+        let topology = Topology::Toroidal { width: width as Coord, height: height as Coord };
+        Ok(Self { cells: HashMap::new(), empty, topology, bounding_box: None })
+    }
+
+    /// Sparse copy of the grid (with the same positions), without its `empty` chars
+    pub fn from_grid(grid: &impl CharGrid, empty: char) -> Self {
+        let mut sparse = Self::new(empty);
+        for (pos, chr) in grid.iter_with_position() {
+            // NOTE: Positions of a grid are always far from the coordinate limits
+            sparse.set(pos, chr).unwrap();
+        }
+        sparse
+    }
+
+    /// The position in the plane, wrapped around the edges when toroidal
+    pub fn wrap(&self, pos: &Pos) -> Pos {
+        match self.topology {
+            Topology::Infinite => *pos,
+            Topology::Toroidal { width, height } => Pos { x: pos.x.rem_euclid(width), y: pos.y.rem_euclid(height) },
+        }
+    }
+
+    /// Set the char at the position, setting the empty char clears it.
+    ///
+    /// NOTE: Positions must stay 1 step away from the coordinate limits (so their neighbours are
+    ///   valid positions too), unless toroidal where any position wraps in the plane.
+    pub fn set(&mut self, pos: Pos, chr: char) -> AnyResult<()> {
+        let far_limit = Coord::MIN + 1..=Coord::MAX - 1;
+        let is_far = !far_limit.contains(&pos.x) || !far_limit.contains(&pos.y);
+        if self.topology == Topology::Infinite && is_far {
+            bail!("{pos:?} is too far, coordinates must be in {far_limit:?}");
+        }
+        let pos = self.wrap(&pos);
+        if chr == self.empty {
+            self.remove(&pos);
+            return Ok(());
+        }
+        self.cells.insert(pos, chr);
+        self.bounding_box = Some(extend_box(self.bounding_box, pos));
+        Ok(())
+    }
+
+    /// Clear the char at the position, returns it if it was set
+    pub fn remove(&mut self, pos: &Pos) -> Option<char> {
+        let pos = self.wrap(pos);
+        let chr = self.cells.remove(&pos)?;
+        // NOTE: The box can only shrink when a char on its edge is removed
+        if let Some((min, max)) = self.bounding_box {
+            if pos.x == min.x || pos.x == max.x || pos.y == min.y || pos.y == max.y {
+                self.bounding_box = self.cells.keys()
+                    .fold(None, |bounding_box, &pos| Some(extend_box(bounding_box, pos)));
+            }
+        }
+        Some(chr)
+    }
+
+    /// Number of chars set
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Top-left & bottom-right corners (inclusive) of the set chars, or of the whole plane when
+    /// toroidal. `None` for an infinite grid without any char set.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        match self.topology {
+            Topology::Infinite => self.bounding_box,
+            Topology::Toroidal { width, height } => Some((Pos { x: 0, y: 0 }, Pos { x: width - 1, y: height - 1 })),
+        }
+    }
+
+    /// The chars set, with their position (in no particular order)
+    pub fn cells(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.cells.iter().map(|(&pos, &chr)| (pos, chr))
+    }
+
+    /// Dense copy of the chars within the bounds, the top-left corner being at (0, 0)
+    pub fn to_grid(&self) -> AnyResult<Grid> {
+        let Some((min, max)) = self.bounds() else {
+            bail!("Empty grid");
+        };
+        // NOTE: Checked before building it, as it could take all the memory
+        check_coord_limit("wide", "columns", (max.x as i64 - min.x as i64 + 1) as usize)?;
+        check_coord_limit("tall", "lines", (max.y as i64 - min.y as i64 + 1) as usize)?;
+        let data = (min.y..=max.y)
+            .map(|y| (min.x..=max.x).map(|x| self.get_char(&Pos { x, y })).collect())
+            .collect();
+        Grid::new(data)
+    }
+
+    fn get_char(&self, pos: &Pos) -> char {
+        self.cells.get(&self.wrap(pos)).copied().unwrap_or(self.empty)
+    }
+}
+
+/// Smallest box with the given box (if any) and the position
+fn extend_box(bounding_box: Option<(Pos, Pos)>, pos: Pos) -> (Pos, Pos) {
+    match bounding_box {
+        None => (pos, pos),
+        Some((min, max)) => (
+            Pos { x: min.x.min(pos.x), y: min.y.min(pos.y) },
+            Pos { x: max.x.max(pos.x), y: max.y.max(pos.y) },
+        ),
+    }
+}
+
+impl CharGrid for SparseGrid {
+    fn get_at(&self, pos: &Pos) -> Option<char> {
+        Some(self.get_char(pos))
+    }
+
+    fn iter_with_position(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        let ranges = self.bounds().map(|(min, max)| (min.y..=max.y, min.x..=max.x));
+        ranges.into_iter().flat_map(move |(y_range, x_range)| {
+            y_range.flat_map(move |y| x_range.clone().map(move |x| (Pos { x, y }, self.get_char(&Pos { x, y }))))
+        })
+    }
+
+    /// NOTE: On a toroidal grid, positions are wrapped around the edges
+    fn neighbours(&self, pos: &Pos) -> impl Iterator<Item = (Direction, Pos, char)> + '_ {
+        // NOTE: Wrapped first, a toroidal grid accepts positions at the coordinate limits
+        let pos = self.wrap(pos);
+        // NOTE: Steps past the coordinate limits are skipped (only possible when infinite)
+        Direction::ALL.into_iter().filter_map(move |dir| {
+            let next_pos = self.wrap(&dir.checked_next_pos(&pos)?);
+            Some((dir, next_pos, self.get_char(&next_pos)))
+        })
+    }
+}
+
+impl fmt::Display for SparseGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((min, max)) = self.bounds() else {
+            return Ok(());
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                write!(f, "{}", self.get_char(&Pos { x, y }))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsing::parse_char_grid;
+
+    #[test]
+    fn test_infinite_grid() {
+        let mut sparse = SparseGrid::new('.');
+        assert_eq!(sparse.bounds(), None);
+        assert_eq!(sparse.to_string(), "");
+        assert_eq!(sparse.get_at(&Pos { x: 1_000_000, y: -1_000_000 }), Some('.'));
+
+        sparse.set(Pos { x: -2, y: 1 }, '#').unwrap();
+        sparse.set(Pos { x: 1, y: -1 }, '@').unwrap();
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.bounds(), Some((Pos { x: -2, y: -1 }, Pos { x: 1, y: 1 })));
+        assert_eq!(sparse.get_at(&Pos { x: -2, y: 1 }), Some('#'));
+        assert_eq!(sparse.to_string(), "...@\n....\n#...\n");
+
+        // Setting the empty char clears it, and the box shrinks
+        sparse.set(Pos { x: 1, y: -1 }, '.').unwrap();
+        assert_eq!(sparse.len(), 1);
+        assert_eq!(sparse.cells().collect::<Vec<_>>(), [(Pos { x: -2, y: 1 }, '#')]);
+        assert_eq!(sparse.bounds(), Some((Pos { x: -2, y: 1 }, Pos { x: -2, y: 1 })));
+        assert_eq!(sparse.remove(&Pos { x: -2, y: 1 }), Some('#'));
+        assert_eq!(sparse.remove(&Pos { x: -2, y: 1 }), None);
+        assert_eq!(sparse.bounds(), None);
+        assert!(sparse.is_empty());
+    }
+
+    #[test]
+    fn test_coordinate_limits() {
+        let mut sparse = SparseGrid::new('.');
+        assert!(sparse.set(Pos { x: Coord::MAX - 1, y: 0 }, '#').is_ok());
+        assert!(sparse.set(Pos { x: Coord::MIN + 1, y: 0 }, '#').is_ok());
+        assert_eq!(
+            sparse.set(Pos { x: Coord::MAX, y: 0 }, '#').unwrap_err().to_string(),
+            "Pos(x: 2147483647, y: 0) is too far, coordinates must be in -2147483647..=2147483646",
+        );
+        // The bounding box is way too big to be a dense grid
+        assert_eq!(
+            sparse.to_grid().unwrap_err().to_string(),
+            "Grid is too wide: 4294967294 columns, at most 2147483647 are supported",
+        );
+        // Neighbours past the limits are skipped
+        let corner = Pos { x: Coord::MAX, y: Coord::MIN };
+        assert_eq!(sparse.get_at(&corner), Some('.'));
+        let neighbours: Vec<Direction> = sparse.neighbours(&corner).map(|(dir, _, _)| dir).collect();
+        assert_eq!(neighbours, vec![Direction::Left, Direction::BottomLeft, Direction::Bottom]);
+        assert_eq!(Direction::Right.checked_next_pos(&corner), None);
+        assert_eq!(Direction::Left.checked_next_pos(&corner), Some(Pos { x: Coord::MAX - 1, y: Coord::MIN }));
+    }
+
+    #[test]
+    fn test_toroidal_grid() {
+        let mut torus = SparseGrid::toroidal(3, 2, '.').unwrap();
+        assert_eq!(torus.bounds(), Some((Pos { x: 0, y: 0 }, Pos { x: 2, y: 1 })));
+        assert_eq!(torus.to_string(), "...\n...\n");
+
+        torus.set(Pos { x: -1, y: 0 }, '#').unwrap();
+        torus.set(Pos { x: 3, y: 3 }, '@').unwrap();
+        assert_eq!(torus.to_string(), "..#\n@..\n");
+        assert_eq!(torus.get_at(&Pos { x: 5, y: -2 }), Some('#'));
+        assert_eq!(torus.wrap(&Pos { x: -4, y: 7 }), Pos { x: 2, y: 1 });
+        // Any position wraps in the plane
+        torus.set(Pos { x: Coord::MAX, y: Coord::MIN }, '%').unwrap();
+        assert_eq!(torus.get_at(&Pos { x: 1, y: 0 }), Some('%'));
+        torus.remove(&Pos { x: 1, y: 0 });
+
+        let neighbours: Vec<(Direction, Pos, char)> = torus.neighbours(&Pos { x: 0, y: 0 })
+            .filter(|&(_, _, chr)| chr != '.')
+            .collect();
+        // NOTE: Only 2 lines, so the line above is also the line below
+        assert_eq!(neighbours, vec![
+            (Direction::Top, Pos { x: 0, y: 1 }, '@'),
+            (Direction::Left, Pos { x: 2, y: 0 }, '#'),
+            (Direction::Bottom, Pos { x: 0, y: 1 }, '@'),
+        ]);
+        assert_eq!(torus.neighbours(&Pos { x: 0, y: 0 }).count(), 8);
+        // Even for positions at the coordinate limits
+        let far_neighbours: Vec<_> = torus.neighbours(&Pos { x: Coord::MAX, y: 0 }).collect();
+        assert_eq!(far_neighbours, torus.neighbours(&Pos { x: 1, y: 0 }).collect::<Vec<_>>());
+
+        assert_eq!(SparseGrid::toroidal(0, 2, '.').unwrap_err().to_string(), "Empty toroidal grid of 0x2");
+    }
+
+    #[test]
+    fn test_same_api_as_dense_grid() {
+        let dense = parse_char_grid("..#\n.@.\n...\n").unwrap();
+        let sparse = SparseGrid::from_grid(&dense, '.');
+        assert_eq!(sparse.len(), 2);
+        // The bounding box only has the set chars
+        assert_eq!(sparse.to_string(), ".#\n@.\n");
+        assert_eq!(sparse.to_grid().unwrap(), parse_char_grid(".#\n@.\n").unwrap());
+
+        fn count_around(grid: &impl CharGrid, pos: &Pos, chr: char) -> usize {
+            grid.neighbours(pos).filter(|&(_, _, found)| found == chr).count()
+        }
+        let center = Pos { x: 1, y: 1 };
+        assert_eq!(count_around(&dense, &center, '#'), 1);
+        assert_eq!(count_around(&sparse, &center, '#'), 1);
+        assert_eq!(count_around(&dense, &Pos { x: 0, y: 0 }, '.'), 2);
+        // ..but the sparse grid has chars all around
+        assert_eq!(count_around(&sparse, &Pos { x: 0, y: 0 }, '.'), 7);
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::strategies;
+
+    proptest! {
+        #[test]
+        fn sparse_matches_dense(rows in strategies::letter_grid(&['.', '.', '.', '#', '@'], 8, 8)) {
+            let dense = Grid::new(rows).unwrap();
+            let sparse = SparseGrid::from_grid(&dense, '.');
+            for (pos, chr) in dense.iter_with_position() {
+                prop_assert_eq!(sparse.get_at(&pos), Some(chr));
+                let dense_neighbours: Vec<_> = dense.neighbours(&pos).collect();
+                let sparse_neighbours: Vec<_> = sparse.neighbours(&pos)
+                    .filter(|(_, pos, _)| dense.get_at(pos).is_some())
+                    .collect();
+                prop_assert_eq!(dense_neighbours, sparse_neighbours);
+            }
+            prop_assert_eq!(sparse.len(), dense.iter_with_position().filter(|&(_, chr)| chr != '.').count());
+            // Same chars, in the same order, within the bounding box
+            if let Some((min, _)) = sparse.bounds() {
+                let in_box: Vec<(Pos, char)> = dense.iter_with_position()
+                    .filter(|(pos, _)| {
+                        let (min, max) = sparse.bounds().unwrap();
+                        (min.x..=max.x).contains(&pos.x) && (min.y..=max.y).contains(&pos.y)
+                    })
+                    .collect();
+                prop_assert_eq!(sparse.iter_with_position().collect::<Vec<_>>(), in_box);
+                prop_assert_eq!(sparse.to_grid().unwrap().get_at(&Pos { x: 0, y: 0 }), dense.get_at(&min));
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Proptest strategies for the shapes of inputs we get in puzzles.
+
+use proptest::prelude::*;
+
+/// Reports of levels, mostly "almost" monotonic with small deltas, to get a good mix of safe,
+/// fixable and unsafe reports (fully random levels would nearly always be unsafe).
+pub fn report(min_len: usize, max_len: usize) -> impl Strategy<Value = Vec<i32>> {
+    let monotonic = (1..100i32, prop::bool::ANY, prop::collection::vec(0..5i32, min_len..=max_len))
+        .prop_map(|(start, increasing, deltas)| {
+            let sign = if increasing { 1 } else { -1 };
+            deltas.iter()
+                .scan(start, |level, delta| {
+                    let current = *level;
+                    *level += sign * delta;
+                    Some(current)
+                })
+                .collect::<Vec<_>>()
+        });
+    let random = prop::collection::vec(1..100i32, min_len..=max_len);
+    prop_oneof![
+        3 => monotonic.clone(),
+        // Monotonic with one level broken
+        2 => (monotonic, any::<prop::sample::Index>(), 1..100i32).prop_map(|(mut report, idx, value)| {
+            if !report.is_empty() {
+                let idx = idx.index(report.len());
+                report[idx] = value;
+            }
+            report
+        }),
+        1 => random,
+    ]
+}
+
+/// Corrupted memory, made of valid & corrupted instructions and gibberish.
+pub fn corrupted_memory() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        3 => (0..1000u32, 0..1000u32).prop_map(|(n1, n2)| format!("mul({n1},{n2})")),
+        1 => Just("do()".to_string()),
+        1 => Just("don't()".to_string()),
+        // Near-miss instructions
+        2 => prop::sample::select(vec![
+            "mul", "mul(", "mul(1", "mul(1,", "mul(1,2", "mul[3,7]", "mul(32,64]", "mul ( 2 , 4 )",
+            "mul(,1)", "mul(1,)", "mul(-1,2)", "mul(+1,2)", "mul(99999999999,1)", "mul(1234,5)", "mul(0012,3)",
+            "do", "do(", "don't", "don't(", "dont()", "undo()",
+        ]).prop_map(str::to_string),
+        3 => "[a-z0-9(),'!@#$%^&*\\[\\]{} \n]{1,5}",
+    ];
+    prop::collection::vec(fragment, 0..30).prop_map(|fragments| fragments.concat())
+}
+
+/// Grids of letters, as lines of the same width.
+pub fn letter_grid(letters: &'static [char], max_width: usize, max_height: usize) -> impl Strategy<Value = Vec<Vec<char>>> {
+    (1..=max_width, 1..=max_height).prop_flat_map(move |(width, height)| {
+        let line = prop::collection::vec(prop::sample::select(letters), width);
+        prop::collection::vec(line, height)
+    })
+}
+
+/// Text version of a grid, as given in puzzle inputs.
+pub fn grid_to_input(grid: &[Vec<char>]) -> String {
+    grid.iter()
+        .map(|line| line.iter().collect::<String>() + "\n")
+        .collect()
+}
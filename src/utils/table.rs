@@ -0,0 +1,91 @@
+use std::fmt;
+
+/// Text table, with each column as wide as its widest cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    pub fn add_row(&mut self, row: Vec<String>) {
+        assert_eq!(row.len(), self.headers.len(), "a row must have a cell per column");
+        self.rows.push(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn column_widths(&self) -> Vec<usize> {
+        (0..self.headers.len())
+            .map(|col_idx| {
+                std::iter::once(&self.headers)
+                    .chain(self.rows.iter())
+                    .map(|row| row[col_idx].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.column_widths();
+        let write_row = |f: &mut fmt::Formatter<'_>, row: &[String]| {
+            let cells: Vec<String> = row.iter()
+                .zip(widths.iter())
+                .map(|(cell, &width)| format!("{cell:width$}"))
+                .collect();
+            // NOTE: No trailing spaces after the last column
+            writeln!(f, "{}", cells.join(" | ").trim_end())
+        };
+
+        write_row(f, &self.headers)?;
+        let separators: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        writeln!(f, "{}", separators.join("-+-"))?;
+        for row in &self.rows {
+            write_row(f, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut table = Table::new(&["#", "Name", "Note"]);
+        assert!(table.is_empty());
+        table.add_row(vec!["1".to_string(), "short".to_string(), "".to_string()]);
+        table.add_row(vec!["10".to_string(), "longer née".to_string(), "ok".to_string()]);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.to_string(), "\
+#  | Name       | Note
+---+------------+-----
+1  | short      |
+10 | longer née | ok
+");
+    }
+
+    #[test]
+    #[should_panic = "a row must have a cell per column"]
+    fn test_row_with_missing_cell() {
+        let mut table = Table::new(&["a", "b"]);
+        table.add_row(vec!["1".to_string()]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result as AnyResult};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const WATCHED_DIRS: &[&str] = &["src/days", "inputs"];
+
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+fn snapshot_dir(dir: &Path, snapshot: &mut Snapshot) -> AnyResult<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read dir {}", dir.display()))? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            snapshot_dir(&entry.path(), snapshot)?;
+        } else {
+            snapshot.insert(entry.path(), metadata.modified()?);
+        }
+    }
+    Ok(())
+}
+
+fn take_snapshot(root: &Path) -> AnyResult<Snapshot> {
+    let mut snapshot = Snapshot::new();
+    for dir in WATCHED_DIRS {
+        snapshot_dir(&root.join(dir), &mut snapshot)?;
+    }
+    Ok(snapshot)
+}
+
+/// One line of results, with the key used to match it with the same result of the previous run
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResultLine {
+    key: String,
+    line: String,
+}
+impl ResultLine {
+    fn new(line: &str) -> Self {
+        // Part results look like `✅ Part1: 42 (== expected)`,
+        // test results look like `test days::day02::tests::test_parsing ... ok`.
+        let key = if let Some(test_line) = line.strip_prefix("test ") {
+            test_line.split(" ...").next().unwrap_or(test_line)
+        } else if let Some(part_idx) = line.find("Part") {
+            line[part_idx..].split(':').next().unwrap_or(line)
+        } else {
+            line
+        };
+        Self { key: key.to_string(), line: line.to_string() }
+    }
+}
+
+fn cargo(subcommand: &str, args: &[&str]) -> AnyResult<Output> {
+    let mut cmd = Command::new(env::var("CARGO").unwrap_or("cargo".to_string()));
+    cmd.arg(subcommand);
+    // Build the same profile & features as the ones we're running
+    if !cfg!(debug_assertions) {
+        cmd.arg("--release");
+    }
+    if cfg!(feature = "alloc-stats") {
+        cmd.args(["--features", "alloc-stats"]);
+    }
+    cmd.args(args);
+    cmd.current_dir(env!("CARGO_MANIFEST_DIR"));
+    cmd.output().context("Failed to run cargo")
+}
+
+/// Rebuild, then run the day's parts and examples (its tests).
+/// Returns `None` when the build failed.
+fn build_and_run(exe_path: &Path, day_name: &str) -> AnyResult<Option<Vec<ResultLine>>> {
+    let build = cargo("build", &["--quiet"])?;
+    if !build.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&build.stderr));
+        return Ok(None);
+    }
+
+    // NOTE: the current executable has just been rebuilt, running it again runs the new code
+    let run = Command::new(exe_path)
+        .arg(day_name)
+        .output()
+        .context("Failed to run the day")?;
+    // NOTE: unconfirmed results are on stderr, we want them too
+    let run_output = String::from_utf8_lossy(&run.stdout).to_string() + &String::from_utf8_lossy(&run.stderr);
+
+    let test_filter = format!("days::{day_name}::");
+    let tests = cargo("test", &["--quiet", &test_filter, "--", "--format", "pretty"])?;
+    let tests_output = String::from_utf8_lossy(&tests.stdout).to_string();
+    let test_lines = tests_output.lines().filter(|line| line.starts_with("test ") && line.contains(" ... "));
+
+    let results = run_output.lines()
+        .filter(|line| !line.is_empty())
+        .chain(test_lines)
+        .map(ResultLine::new)
+        .collect();
+    Ok(Some(results))
+}
+
+/// Compact diff of results: only changed, new & removed results, and the number of unchanged ones.
+fn diff_results(previous: &[ResultLine], current: &[ResultLine]) -> Vec<String> {
+    let mut diff = vec![];
+    let mut num_unchanged = 0;
+    for result in current {
+        match previous.iter().find(|prev| prev.key == result.key) {
+            Some(prev) if prev.line == result.line => num_unchanged += 1,
+            Some(prev) => {
+                diff.push(format!("  - {}", prev.line));
+                diff.push(format!("  + {}", result.line));
+            }
+            None => diff.push(format!("  + {}", result.line)),
+        }
+    }
+    for prev in previous {
+        if !current.iter().any(|result| result.key == prev.key) {
+            diff.push(format!("  - {}", prev.line));
+        }
+    }
+    diff.push(format!("  ({num_unchanged} unchanged)"));
+    diff
+}
+
+pub fn watch(day_name: &str) -> AnyResult<()> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // NOTE: must be resolved before the first rebuild, which replaces the executable file
+    //   (after that the path of the running executable ends with ` (deleted)` on Linux)
+    let exe_path = env::current_exe()?;
+    let mut previous_results: Option<Vec<ResultLine>> = None;
+    let mut last_snapshot: Option<Snapshot> = None;
+
+    println!("Watching {} for changes (Ctrl-C to stop)", WATCHED_DIRS.join(", "));
+    loop {
+        let snapshot = take_snapshot(&root)?;
+        if last_snapshot.as_ref() == Some(&snapshot) {
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        last_snapshot = Some(snapshot);
+
+        println!();
+        println!("=>> Change detected, rebuilding & running {day_name}...");
+        let Some(results) = build_and_run(&exe_path, day_name)? else {
+            println!("❌ Build failed, waiting for the next change");
+            continue;
+        };
+        for result in results.iter() {
+            println!("{}", result.line);
+        }
+        if let Some(previous_results) = previous_results {
+            println!("~~ Changes since previous run:");
+            for diff_line in diff_results(&previous_results, &results) {
+                println!("{diff_line}");
+            }
+        }
+        previous_results = Some(results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_line_keys() {
+        assert_eq!(ResultLine::new("✅ Part1: 42 (== expected)").key, "Part1");
+        assert_eq!(ResultLine::new("-- Part2: 42 ?").key, "Part2");
+        assert_eq!(ResultLine::new("test days::day02::tests::test_parsing ... ok").key, "days::day02::tests::test_parsing");
+        assert_eq!(ResultLine::new("=>> day02 - Red-Nosed Reports").key, "=>> day02 - Red-Nosed Reports");
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let previous: Vec<_> = ["❌ Part1: Not implemented", "❌ Part2: Not implemented", "test a ... ok", "test b ... ok"]
+            .into_iter()
+            .map(ResultLine::new)
+            .collect();
+        let current: Vec<_> = ["-- Part1: 42 ?", "❌ Part2: Not implemented", "test a ... FAILED", "test c ... ok"]
+            .into_iter()
+            .map(ResultLine::new)
+            .collect();
+        assert_eq!(diff_results(&previous, &current), vec![
+            "  - ❌ Part1: Not implemented",
+            "  + -- Part1: 42 ?",
+            "  - test a ... ok",
+            "  + test a ... FAILED",
+            "  + test c ... ok",
+            "  - test b ... ok",
+            "  (1 unchanged)",
+        ]);
+    }
+}